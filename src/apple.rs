@@ -0,0 +1,370 @@
+use std::time::Duration;
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin, Startup},
+  asset::{AssetServer, Handle},
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::EventWriter,
+    query::{With, Without},
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource, Single},
+  },
+  image::Image,
+  sprite::Sprite,
+  state::{state::OnEnter, state_scoped::StateScoped},
+  time::{Time, Timer, TimerMode},
+};
+use bevy_world_space::{
+  position::Position,
+  world_init::WorldInitPlugin,
+  world_unit::{WorldUnit, WorldVec2},
+};
+
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  gravity::GravityComponent,
+  movable::{MoveComponent, MovePlugin},
+  player::{Player, PlayerId},
+  rng::{DrawRandom, GameRng, RngPlugin},
+  score::EarnPoint,
+};
+
+#[derive(Clone, Copy)]
+enum AppleKind {
+  /// Worth bonus points.
+  Good,
+  /// Shrinks the umbrella for a while.
+  Bad,
+  /// Widens the umbrella for a while.
+  Super,
+}
+
+impl AppleKind {
+  fn random_kind(rng: &mut fastrand::Rng) -> Self {
+    match rng.u32(0..10) {
+      0..6 => Self::Good,
+      6..9 => Self::Bad,
+      _ => Self::Super,
+    }
+  }
+
+  fn image(&self, apple_assets: &AppleAssets) -> Handle<Image> {
+    match self {
+      Self::Good => apple_assets.good_apple.clone_weak(),
+      Self::Bad => apple_assets.bad_apple.clone_weak(),
+      Self::Super => apple_assets.super_apple.clone_weak(),
+    }
+  }
+}
+
+#[derive(Component)]
+#[require(MoveComponent, GravityComponent)]
+struct Apple {
+  kind: AppleKind,
+}
+
+#[derive(Bundle)]
+struct AppleBundle {
+  sprite: Sprite,
+  pos: Position,
+  apple: Apple,
+  scope: StateScoped<InGame>,
+  move_component: MoveComponent,
+}
+
+impl AppleBundle {
+  const IMG_WIDTH: u32 = 343;
+
+  const RADIUS: WorldUnit = WorldUnit::new(0.8);
+
+  const Z_IDX: f32 = 1.5;
+
+  fn new(kind: AppleKind, pos: WorldVec2, delta: WorldVec2, apple_assets: &AppleAssets) -> Self {
+    Self {
+      sprite: Sprite::from_image(kind.image(apple_assets)),
+      pos: Position::new(pos, 2. * Self::RADIUS, Self::IMG_WIDTH, Self::Z_IDX),
+      apple: Apple { kind },
+      scope: StateScoped(InGame),
+      move_component: MoveComponent { delta },
+    }
+  }
+}
+
+/// Component that identifies the apple vendor.
+#[derive(Component)]
+struct Vendor;
+
+#[derive(Bundle)]
+struct VendorBundle {
+  sprite: Sprite,
+  pos: Position,
+  vendor: Vendor,
+}
+
+impl VendorBundle {
+  const IMG_WIDTH: u32 = 589;
+  const IMG_HEIGHT: u32 = 656;
+  const ASPECT_RATIO: f32 = Self::IMG_HEIGHT as f32 / Self::IMG_WIDTH as f32;
+
+  const WIDTH: WorldUnit = WorldUnit::new(3.9);
+  const HEIGHT: WorldUnit = WorldUnit::new(3.9 * Self::ASPECT_RATIO);
+
+  const Z_IDX: f32 = 0.5;
+}
+
+#[derive(Resource)]
+struct AppleAssets {
+  lady: Handle<Image>,
+  good_apple: Handle<Image>,
+  bad_apple: Handle<Image>,
+  super_apple: Handle<Image>,
+}
+
+#[derive(Resource)]
+struct ApplePluginState {
+  toss_timer: Timer,
+}
+
+impl ApplePluginState {
+  const TOSS_TIMER: Duration = Duration::from_secs(7);
+
+  fn new() -> Self {
+    Self {
+      toss_timer: Timer::new(Self::TOSS_TIMER, TimerMode::Repeating),
+    }
+  }
+}
+
+pub struct ApplePlugin;
+
+impl ApplePlugin {
+  const GOOD_APPLE_POINTS: u32 = 3;
+
+  const BAD_APPLE_SCALE: f32 = 0.6;
+  const SUPER_APPLE_SCALE: f32 = 1.6;
+  const POWER_UP_DURATION: Duration = Duration::from_secs(6);
+
+  fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AppleAssets {
+      lady: asset_server.load("apple/lady.png"),
+      good_apple: asset_server.load("apple/good_apple.png"),
+      bad_apple: asset_server.load("apple/bad_apple.png"),
+      super_apple: asset_server.load("apple/super_apple.png"),
+    });
+  }
+
+  fn reset_toss_timer(mut state: ResMut<ApplePluginState>) {
+    *state = ApplePluginState::new();
+  }
+
+  fn spawn_vendor(mut commands: Commands, apple_assets: Res<AppleAssets>) {
+    commands.spawn(VendorBundle {
+      sprite: Sprite::from_image(apple_assets.lady.clone_weak()),
+      pos: Position::new(
+        WorldVec2::new_normalized(-0.85, -0.78)
+          + WorldVec2 {
+            x: WorldUnit::ZERO,
+            y: VendorBundle::HEIGHT / 2.,
+          },
+        VendorBundle::WIDTH,
+        VendorBundle::IMG_WIDTH,
+        VendorBundle::Z_IDX,
+      ),
+      vendor: Vendor,
+    });
+  }
+
+  fn toss_apples(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<ApplePluginState>,
+    mut rng: ResMut<GameRng>,
+    apple_assets: Res<AppleAssets>,
+    vendor: Single<&Position, With<Vendor>>,
+  ) {
+    if state.toss_timer.tick(time.delta()).just_finished() {
+      let delta = WorldVec2::new(
+        WorldUnit::new(6. + 8. * rng.f32()),
+        WorldUnit::new(14. + 6. * rng.f32()),
+      );
+      commands.spawn(AppleBundle::new(
+        AppleKind::random_kind(&mut rng),
+        vendor.pos + WorldVec2::new(WorldUnit::ZERO, VendorBundle::HEIGHT / 2.),
+        delta,
+        &apple_assets,
+      ));
+    }
+  }
+
+  fn catch_apples(
+    mut commands: Commands,
+    mut earn_point: EventWriter<EarnPoint>,
+    mut umbrellas: Query<(&PlayerId, &Position, &mut Player)>,
+    apple_query: Query<(Entity, &Position, &Apple), Without<Player>>,
+  ) {
+    for (entity, apple_pos, apple) in &apple_query {
+      // Each apple goes to the first umbrella that catches it.
+      let catcher = umbrellas.iter_mut().find(|(_, player_pos, player)| {
+        let diff = apple_pos.pos - player_pos.pos;
+        diff.dot(player.canopy_normal()) >= 0.
+          && diff.length_squared() < (player.width() / 2. + AppleBundle::RADIUS).squared()
+      });
+      let Some((&id, _, mut player)) = catcher else {
+        continue;
+      };
+
+      match apple.kind {
+        AppleKind::Good => {
+          for _ in 0..Self::GOOD_APPLE_POINTS {
+            earn_point.send(EarnPoint { player: Some(id) });
+          }
+        }
+        AppleKind::Bad => player.resize(Self::BAD_APPLE_SCALE, Self::POWER_UP_DURATION),
+        AppleKind::Super => player.resize(Self::SUPER_APPLE_SCALE, Self::POWER_UP_DURATION),
+      }
+      commands.entity(entity).despawn();
+    }
+  }
+
+  fn despawn_apples(mut commands: Commands, query: Query<(Entity, &Position), With<Apple>>) {
+    let min_y = WorldUnit::BOTTOM - AppleBundle::RADIUS;
+    let x_bound = WorldUnit::RIGHT + AppleBundle::RADIUS;
+    for (entity, Position { pos, .. }) in &query {
+      if pos.y < min_y || !(-x_bound..x_bound).contains(&pos.x) {
+        commands.entity(entity).despawn();
+      }
+    }
+  }
+}
+
+impl Plugin for ApplePlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .insert_resource(ApplePluginState::new())
+      .add_systems(
+        Startup,
+        (
+          Self::initialize_plugin,
+          Self::spawn_vendor.after(WorldInitPlugin::world_init),
+        )
+          .chain(),
+      )
+      .add_systems(
+        OnEnter(InGame),
+        Self::reset_toss_timer.after(RngPlugin::reseed_rng),
+      )
+      .add_systems(
+        FixedUpdate,
+        (
+          Self::toss_apples.in_set(DrawRandom::Apples),
+          Self::catch_apples.before(MovePlugin::apply_moves),
+          Self::despawn_apples,
+        )
+          .run_if(GameStatePlugin::playing),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::ecs::query::With;
+  use bevy_world_space::{
+    position::Position,
+    world_unit::{WorldUnit, WorldVec2},
+  };
+  use googletest::prelude::*;
+
+  use crate::{player::Player, score::ScoreResource, test_harness::TestApp};
+
+  use super::{Apple, AppleAssets, AppleBundle, AppleKind, ApplePlugin};
+
+  /// The width of the umbrella before any apple changes it.
+  const FULL_WIDTH: f32 = 7.;
+
+  fn umbrella_width(app: &mut TestApp) -> f32 {
+    let world = app.world_mut();
+    world.query::<&Player>().single(world).width().to_untyped()
+  }
+
+  /// Drops an apple of `kind` just above the umbrella and lets it land.
+  fn catch_apple(app: &mut TestApp, kind: AppleKind) {
+    let world = app.world_mut();
+    let umbrella = world
+      .query_filtered::<&Position, With<Player>>()
+      .single(world)
+      .pos;
+    let apple = AppleBundle::new(
+      kind,
+      umbrella + WorldVec2::new(WorldUnit::ZERO, WorldUnit::new(0.5)),
+      WorldVec2::ZERO,
+      world.resource::<AppleAssets>(),
+    );
+    world.spawn(apple);
+    app.step().step();
+  }
+
+  fn apple_count(app: &mut TestApp) -> usize {
+    let world = app.world_mut();
+    world.query::<&Apple>().iter(world).count()
+  }
+
+  #[gtest]
+  fn good_apples_are_worth_points() {
+    let mut app = TestApp::new();
+    app.start_playing();
+
+    catch_apple(&mut app, AppleKind::Good);
+
+    expect_that!(apple_count(&mut app), eq(0));
+    expect_that!(
+      app.world().resource::<ScoreResource>().points,
+      eq(ApplePlugin::GOOD_APPLE_POINTS)
+    );
+    expect_that!(umbrella_width(&mut app), approx_eq(FULL_WIDTH));
+  }
+
+  #[gtest]
+  fn bad_apples_shrink_the_umbrella_for_a_while() {
+    let mut app = TestApp::new();
+    app.start_playing();
+
+    catch_apple(&mut app, AppleKind::Bad);
+
+    expect_that!(apple_count(&mut app), eq(0));
+    expect_that!(app.world().resource::<ScoreResource>().points, eq(0));
+    expect_that!(
+      umbrella_width(&mut app),
+      approx_eq(FULL_WIDTH * ApplePlugin::BAD_APPLE_SCALE)
+    );
+
+    app.run_for(ApplePlugin::POWER_UP_DURATION - Duration::from_millis(500));
+    expect_that!(
+      umbrella_width(&mut app),
+      approx_eq(FULL_WIDTH * ApplePlugin::BAD_APPLE_SCALE)
+    );
+    app.run_for(Duration::from_millis(500));
+    expect_that!(umbrella_width(&mut app), approx_eq(FULL_WIDTH));
+  }
+
+  #[gtest]
+  fn super_apples_widen_the_umbrella_for_a_while() {
+    let mut app = TestApp::new();
+    app.start_playing();
+
+    catch_apple(&mut app, AppleKind::Super);
+
+    expect_that!(apple_count(&mut app), eq(0));
+    expect_that!(
+      umbrella_width(&mut app),
+      approx_eq(FULL_WIDTH * ApplePlugin::SUPER_APPLE_SCALE)
+    );
+
+    app.run_for(ApplePlugin::POWER_UP_DURATION);
+    expect_that!(umbrella_width(&mut app), approx_eq(FULL_WIDTH));
+  }
+}
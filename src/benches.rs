@@ -0,0 +1,156 @@
+//! Benchmarks for the gameplay systems that run every fixed tick. They are
+//! ignored by default, since they only mean something in release mode:
+//!
+//! ```sh
+//! cargo test --release benches -- --ignored --nocapture --test-threads=1
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+  asset::Handle,
+  ecs::{
+    entity::Entity,
+    event::Events,
+    query::{Or, With},
+    schedule::{IntoSystemConfigs, Schedule},
+    world::World,
+  },
+};
+use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+use googletest::prelude::*;
+
+use crate::{
+  collider::{CollisionPlugin, RainCollision},
+  gravity::GravityPlugin,
+  lives::LoseLife,
+  movable::{MoveComponent, MovePlugin},
+  npc::{Npc, NpcPlugin},
+  puddle::RainLanded,
+  rain::{Rain, RainBundle, RainKind, RainPlugin, RainPool},
+  splash::Splash,
+  test_harness::TestApp,
+};
+
+/// How many times each schedule is timed after warming up.
+const ITERATIONS: u32 = 200;
+const WARMUP_ITERATIONS: u32 = 20;
+
+const RAIN_COUNTS: [usize; 3] = [100, 1_000, 5_000];
+const NPC_COUNTS: [usize; 3] = [5, 20, 50];
+
+/// Replaces whatever the last sample left behind with `rain` drops scattered
+/// over the screen and `npcs` NPCs spread along the ground. The systems move,
+/// soak and recycle drops, so every sample starts over from the same scene.
+fn populate(world: &mut World, rain: usize, npcs: usize) {
+  let stale: Vec<Entity> = world
+    .query_filtered::<Entity, Or<(With<Rain>, With<Npc>)>>()
+    .iter(world)
+    .collect();
+  for entity in stale {
+    world.despawn(entity);
+  }
+  world.insert_resource(RainPool::default());
+  // The benched schedules run outside `FixedUpdate`, so nothing else clears
+  // the events they send.
+  world.resource_mut::<Events<RainCollision>>().clear();
+  world.resource_mut::<Events<Splash>>().clear();
+  world.resource_mut::<Events<RainLanded>>().clear();
+  world.resource_mut::<Events<LoseLife>>().clear();
+
+  let mut rng = fastrand::Rng::with_seed(0);
+  for _ in 0..rain {
+    let pos = WorldVec2::new_normalized(rng.f32() * 2. - 1., rng.f32() * 1.5 - 0.5);
+    world.spawn((
+      RainBundle::new(Handle::default(), pos, RainKind::Drop),
+      MoveComponent {
+        delta: WorldVec2::new(WorldUnit::ZERO, WorldUnit::new(-10.)),
+      },
+    ));
+  }
+  for idx in 0..npcs {
+    let x = (idx as f32 + 0.5) / npcs as f32 * 1.6 - 0.8;
+    NpcPlugin::spawn_npc_at(world, WorldVec2::new_normalized(x, -0.7));
+  }
+}
+
+/// The mean time it takes to run `systems` once on a freshly populated
+/// scene. Populating the scene isn't timed.
+fn time_systems<M>(
+  app: &mut TestApp,
+  rain: usize,
+  npcs: usize,
+  systems: impl IntoSystemConfigs<M>,
+) -> Duration {
+  let mut schedule = Schedule::default();
+  schedule.add_systems(systems);
+  let world = app.world_mut();
+  for _ in 0..WARMUP_ITERATIONS {
+    populate(world, rain, npcs);
+    schedule.run(world);
+  }
+
+  let mut total = Duration::ZERO;
+  for _ in 0..ITERATIONS {
+    populate(world, rain, npcs);
+    let start = Instant::now();
+    schedule.run(world);
+    total += start.elapsed();
+  }
+  total / ITERATIONS
+}
+
+/// Times the systems made by `systems` with every count of drops and NPCs.
+fn bench<M, S: IntoSystemConfigs<M>>(name: &str, systems: impl Fn() -> S) {
+  let mut app = TestApp::new();
+  app.wait_until(NpcPlugin::roster_loaded).start_playing();
+  for rain in RAIN_COUNTS {
+    for npcs in NPC_COUNTS {
+      let mean = time_systems(&mut app, rain, npcs, systems());
+      println!("{name:<36} {rain:>6} drops {npcs:>3} NPCs {mean:>10.1?}");
+    }
+  }
+}
+
+#[gtest]
+#[ignore = "benchmark"]
+fn bench_apply_moves() {
+  bench("MovePlugin::apply_moves", || MovePlugin::apply_moves);
+}
+
+#[gtest]
+#[ignore = "benchmark"]
+fn bench_apply_gravity() {
+  bench("GravityPlugin::apply_gravity", || {
+    GravityPlugin::apply_gravity
+  });
+}
+
+#[gtest]
+#[ignore = "benchmark"]
+fn bench_rebuild_rain_grid() {
+  bench("CollisionPlugin::rebuild_rain_grid", || {
+    CollisionPlugin::rebuild_rain_grid
+  });
+}
+
+#[gtest]
+#[ignore = "benchmark"]
+fn bench_rain_collisions() {
+  bench("rain collisions", || {
+    (
+      CollisionPlugin::rebuild_rain_grid,
+      CollisionPlugin::handle_rain_collisions,
+      NpcPlugin::soak_npcs,
+    )
+      .chain()
+  });
+}
+
+#[gtest]
+#[ignore = "benchmark"]
+fn bench_recycle_raindrops() {
+  bench("RainPlugin::recycle_raindrops", || {
+    RainPlugin::recycle_raindrops
+  });
+}
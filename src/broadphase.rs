@@ -0,0 +1,81 @@
+use bevy::{
+  ecs::{entity::Entity, system::Resource},
+  math::IVec2,
+  utils::HashMap,
+};
+use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+
+/// Buckets raindrops into a uniform grid, so colliders only need to test the
+/// drops near them.
+#[derive(Resource, Default)]
+pub struct RainGrid {
+  /// Cells are kept around when they empty out, so rebuilding the grid every
+  /// tick doesn't reallocate them.
+  cells: HashMap<IVec2, Vec<Entity>>,
+}
+
+impl RainGrid {
+  const CELL_SIZE: WorldUnit = WorldUnit::new(4.);
+
+  fn cell(pos: WorldVec2) -> IVec2 {
+    (pos.to_untyped() / Self::CELL_SIZE.to_untyped())
+      .floor()
+      .as_ivec2()
+  }
+
+  pub fn clear(&mut self) {
+    for cell in self.cells.values_mut() {
+      cell.clear();
+    }
+  }
+
+  pub fn insert(&mut self, entity: Entity, pos: WorldVec2) {
+    self.cells.entry(Self::cell(pos)).or_default().push(entity);
+  }
+
+  /// Every drop in a cell that overlaps the square of half width `extent`
+  /// around `center`. This may include drops a little outside the square.
+  pub fn near(&self, center: WorldVec2, extent: WorldUnit) -> impl Iterator<Item = Entity> + '_ {
+    let offset = WorldVec2::new(extent, extent);
+    let min = Self::cell(center - offset);
+    let max = Self::cell(center + offset);
+    (min.y..=max.y)
+      .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+      .filter_map(|cell| self.cells.get(&cell))
+      .flatten()
+      .copied()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::ecs::entity::Entity;
+  use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+  use googletest::prelude::*;
+
+  use super::RainGrid;
+
+  #[gtest]
+  fn only_nearby_drops_are_found() {
+    let mut grid = RainGrid::default();
+    let near = Entity::from_raw(0);
+    let far = Entity::from_raw(1);
+    grid.insert(
+      near,
+      WorldVec2::new(WorldUnit::new(1.), WorldUnit::new(-1.)),
+    );
+    grid.insert(
+      far,
+      WorldVec2::new(WorldUnit::new(20.), WorldUnit::new(10.)),
+    );
+
+    let found: Vec<_> = grid.near(WorldVec2::ZERO, WorldUnit::new(2.)).collect();
+    expect_that!(found, elements_are![eq(&near)]);
+
+    grid.clear();
+    expect_that!(
+      grid.near(WorldVec2::ZERO, WorldUnit::new(2.)).count(),
+      eq(0)
+    );
+  }
+}
@@ -0,0 +1,367 @@
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventWriter},
+    query::{With, Without},
+    schedule::IntoSystemConfigs,
+    system::{Query, Res, ResMut},
+  },
+  math::{Quat, Vec2},
+};
+use bevy_world_space::{
+  position::Position,
+  world_unit::{WorldRect, WorldUnit, WorldVec2},
+};
+
+use crate::{
+  broadphase::RainGrid,
+  game_state::GameStatePlugin,
+  movable::{MoveComponent, MovePlugin},
+  rain::{InPlay, Rain, RainKind},
+  splash::Splash,
+};
+
+/// The outline of a collider, centered on its `Position` and turned with its
+/// rotation.
+pub enum Shape {
+  /// The upper half of a circle. Only things above the flat side touch it.
+  HalfCircle(WorldUnit),
+  Rect(WorldRect),
+  /// A convex polygon with its vertices in counterclockwise order.
+  ConvexPolygon(Vec<WorldVec2>),
+}
+
+impl Shape {
+  const RIM_NORMAL_Y: f32 = 0.5;
+
+  /// The distance from the center to the furthest point of the shape, in any
+  /// rotation.
+  fn bounding_radius(&self) -> WorldUnit {
+    match self {
+      Self::HalfCircle(radius) => *radius,
+      Self::Rect(rect) => WorldUnit::new(
+        WorldVec2::new(rect.width, rect.height)
+          .to_untyped()
+          .length()
+          / 2.,
+      ),
+      Self::ConvexPolygon(vertices) => vertices
+        .iter()
+        .map(|vertex| WorldUnit::new(vertex.to_untyped().length()))
+        .fold(WorldUnit::ZERO, WorldUnit::max),
+    }
+  }
+
+  /// True if `normal`, before rotation, lies on the rim of a half circle,
+  /// where it is steep enough for heavy drops to punch through.
+  fn is_rim(&self, normal: Vec2) -> bool {
+    matches!(self, Self::HalfCircle(_)) && normal.y < Self::RIM_NORMAL_Y
+  }
+
+  /// The outward surface normal where a drop of `radius` centered at `offset`
+  /// touches the shape, if it does. Both are relative to the shape's center,
+  /// before rotation.
+  fn contact(&self, offset: Vec2, radius: f32) -> Option<Vec2> {
+    match self {
+      Self::HalfCircle(circle_radius) => (offset.y >= 0.
+        && offset.length_squared() < (circle_radius.to_untyped() + radius).powi(2))
+      .then(|| offset.normalize_or(Vec2::Y)),
+      Self::Rect(rect) => {
+        let half_size = Vec2::new(rect.width.to_untyped(), rect.height.to_untyped()) / 2.;
+        let outside = offset - offset.clamp(-half_size, half_size);
+        if outside != Vec2::ZERO {
+          return (outside.length_squared() < radius.powi(2)).then(|| outside.normalize());
+        }
+        // The center of the drop is inside, so push it out through the
+        // nearest side.
+        let depth = half_size - offset.abs();
+        Some(if depth.x < depth.y {
+          Vec2::new(offset.x.signum(), 0.)
+        } else {
+          Vec2::new(0., offset.y.signum())
+        })
+      }
+      Self::ConvexPolygon(vertices) => {
+        let vertices: Vec<Vec2> = vertices.iter().map(|vertex| vertex.to_untyped()).collect();
+        let edges = vertices
+          .iter()
+          .zip(vertices.iter().cycle().skip(1))
+          .map(|(&start, &end)| (start, end));
+
+        let mut closest: Option<(f32, Vec2)> = None;
+        let mut shallowest: Option<(f32, Vec2)> = None;
+        for (start, end) in edges {
+          let edge = end - start;
+          let normal = Vec2::new(edge.y, -edge.x).normalize();
+          let depth = -(offset - start).dot(normal);
+          if shallowest.is_none_or(|(shallowest, _)| depth < shallowest) {
+            shallowest = Some((depth, normal));
+          }
+
+          let t = ((offset - start).dot(edge) / edge.length_squared()).clamp(0., 1.);
+          let outside = offset - (start + edge * t);
+          let distance = outside.length_squared();
+          if closest.is_none_or(|(closest, _)| distance < closest) {
+            closest = Some((distance, outside));
+          }
+        }
+
+        match (shallowest, closest) {
+          // Inside every edge, so push the drop out through the nearest one.
+          (Some((depth, normal)), _) if depth >= 0. => Some(normal),
+          (_, Some((distance, outside))) if distance < radius.powi(2) => {
+            Some(outside.normalize_or(Vec2::Y))
+          }
+          _ => None,
+        }
+      }
+    }
+  }
+}
+
+/// Something rain runs into. Solid colliders deflect the rain, while sensors
+/// only report that it touched them.
+#[derive(Component)]
+pub struct Collider {
+  pub shape: Shape,
+  /// The fraction of the rain's speed into the surface that it bounces back
+  /// with.
+  pub restitution: f32,
+  /// The fraction of the rain's speed along the surface that it loses.
+  pub friction: f32,
+  pub sensor: bool,
+}
+
+impl Collider {
+  pub fn solid(shape: Shape, restitution: f32, friction: f32) -> Self {
+    Self {
+      shape,
+      restitution,
+      friction,
+      sensor: false,
+    }
+  }
+
+  pub fn sensor(shape: Shape) -> Self {
+    Self {
+      shape,
+      restitution: 0.,
+      friction: 0.,
+      sensor: true,
+    }
+  }
+
+  /// Where a drop of `radius` at `pos` touches this collider, which sits at
+  /// `collider_pos`.
+  fn contact(&self, collider_pos: &Position, pos: WorldVec2, radius: WorldUnit) -> Option<Contact> {
+    let rotation: Quat = collider_pos.rotation;
+    let offset = (pos - collider_pos.pos).to_untyped();
+    let local_offset = (rotation.inverse() * offset.extend(0.)).truncate();
+    self
+      .shape
+      .contact(local_offset, radius.to_untyped())
+      .map(|normal| Contact {
+        normal: (rotation * normal.extend(0.)).truncate(),
+        on_rim: self.shape.is_rim(normal),
+      })
+  }
+}
+
+struct Contact {
+  /// The world space surface normal.
+  normal: Vec2,
+  on_rim: bool,
+}
+
+/// Sent whenever a raindrop hits a solid collider, and on every tick that a
+/// raindrop overlaps a sensor.
+#[derive(Event)]
+pub struct RainCollision {
+  pub rain: Entity,
+  pub collider: Entity,
+  /// The momentum the raindrop passed on to the collider. Always zero for
+  /// sensors.
+  pub impulse: WorldVec2,
+}
+
+pub struct CollisionPlugin;
+
+impl CollisionPlugin {
+  pub fn rebuild_rain_grid(
+    mut grid: ResMut<RainGrid>,
+    rain_query: Query<(Entity, &Position), (With<Rain>, With<InPlay>)>,
+  ) {
+    grid.clear();
+    for (entity, pos) in &rain_query {
+      grid.insert(entity, pos.pos);
+    }
+  }
+
+  pub fn handle_rain_collisions(
+    mut collisions: EventWriter<RainCollision>,
+    mut splashes: EventWriter<Splash>,
+    grid: Res<RainGrid>,
+    colliders: Query<(Entity, &Position, &Collider, Option<&MoveComponent>), Without<Rain>>,
+    mut rain_query: Query<(&Rain, &Position, &mut MoveComponent), With<InPlay>>,
+  ) {
+    for (collider_entity, collider_pos, collider, collider_vel) in &colliders {
+      let collider_vel = collider_vel.map_or(WorldVec2::ZERO, |vel| vel.delta);
+      let extent = collider.shape.bounding_radius() + RainKind::MAX_RADIUS;
+      for rain_entity in grid.near(collider_pos.pos, extent) {
+        let Ok((rain, rain_pos, mut rain_vel)) = rain_query.get_mut(rain_entity) else {
+          continue;
+        };
+        let Some(contact) = collider.contact(collider_pos, rain_pos.pos, rain.kind.radius()) else {
+          continue;
+        };
+        let normal = WorldVec2::new(
+          WorldUnit::new(contact.normal.x),
+          WorldUnit::new(contact.normal.y),
+        );
+        if collider.sensor {
+          collisions.send(RainCollision {
+            rain: rain_entity,
+            collider: collider_entity,
+            impulse: WorldVec2::ZERO,
+          });
+          continue;
+        }
+        if contact.on_rim && rain.kind.pierces_rims() {
+          continue;
+        }
+
+        let relative_vel = rain_vel.delta - collider_vel;
+        let speed_into = normal.dot(relative_vel);
+        if speed_into >= 0. {
+          continue;
+        }
+        let normal_vel = normal * speed_into;
+        let tangent_vel = relative_vel - normal_vel;
+        let restitution = (collider.restitution * rain.kind.bounciness()).min(1.);
+        let old_vel = rain_vel.delta;
+        rain_vel.delta =
+          collider_vel + tangent_vel * (1. - collider.friction) + normal_vel * -restitution;
+        collisions.send(RainCollision {
+          rain: rain_entity,
+          collider: collider_entity,
+          impulse: (old_vel - rain_vel.delta) * rain.kind.mass(),
+        });
+        splashes.send(Splash {
+          pos: rain_pos.pos,
+          normal,
+          speed: WorldUnit::new(-speed_into),
+        });
+      }
+    }
+  }
+}
+
+impl Plugin for CollisionPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<RainCollision>()
+      .init_resource::<RainGrid>()
+      .add_systems(
+        FixedUpdate,
+        (Self::rebuild_rain_grid, Self::handle_rain_collisions)
+          .chain()
+          .before(MovePlugin::apply_moves)
+          .run_if(GameStatePlugin::playing),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::math::Vec2;
+  use bevy_world_space::{
+    position::Position,
+    world_unit::{WorldRect, WorldUnit, WorldVec2},
+  };
+  use googletest::prelude::*;
+
+  use crate::{rain::RainKind, test_harness::TestApp};
+
+  use super::{Collider, RainCollision, Shape};
+
+  fn vec(x: f32, y: f32) -> WorldVec2 {
+    WorldVec2::new(WorldUnit::new(x), WorldUnit::new(y))
+  }
+
+  /// Drops a raindrop falling down and to the right onto the top of
+  /// `collider`, away from everything else on screen. Returns the drop's
+  /// velocity after it hits, and how many collisions were reported.
+  fn drop_rain_on(collider: Collider) -> (WorldVec2, usize) {
+    let mut app = TestApp::new();
+    app.start_playing().track_events::<RainCollision>();
+    let collider_pos = WorldVec2::new_normalized(-0.5, 0.45);
+    let world = app.world_mut();
+    world.spawn((
+      Position::new(collider_pos, WorldUnit::new(4.), 100, 0.),
+      collider,
+    ));
+    let rain = app.spawn_drop(collider_pos + vec(0., 2.2), RainKind::Drop, vec(10., -20.));
+    app.step();
+    (app.velocity(rain), app.event_count::<RainCollision>())
+  }
+
+  #[gtest]
+  fn half_circle_ignores_drops_below_it() {
+    let shape = Shape::HalfCircle(WorldUnit::new(2.));
+
+    expect_that!(shape.contact(Vec2::new(0., 1.), 0.5), some(eq(Vec2::Y)));
+    expect_that!(shape.contact(Vec2::new(0., -1.), 0.5), none());
+  }
+
+  #[gtest]
+  fn drops_inside_a_rect_leave_through_the_nearest_side() {
+    let shape = Shape::Rect(WorldRect::new(WorldUnit::new(4.), WorldUnit::new(2.)));
+
+    expect_that!(shape.contact(Vec2::new(1.8, 0.), 0.1), some(eq(Vec2::X)));
+    expect_that!(
+      shape.contact(Vec2::new(0., -0.9), 0.1),
+      some(eq(Vec2::NEG_Y))
+    );
+    expect_that!(shape.contact(Vec2::new(2.2, 0.), 0.1), none());
+  }
+
+  #[gtest]
+  fn convex_polygon_matches_its_edges() {
+    let triangle = Shape::ConvexPolygon(vec![vec(-1., 0.), vec(1., 0.), vec(0., 1.)]);
+
+    expect_that!(
+      triangle.contact(Vec2::new(0., -0.05), 0.1),
+      some(eq(Vec2::NEG_Y))
+    );
+    expect_that!(
+      triangle.contact(Vec2::new(0., 0.1), 0.1),
+      some(eq(Vec2::NEG_Y))
+    );
+    expect_that!(triangle.contact(Vec2::new(1., 1.), 0.1), none());
+  }
+
+  #[gtest]
+  fn solid_colliders_deflect_rain() {
+    let canopy = || Shape::HalfCircle(WorldUnit::new(2.));
+
+    let (slippery, collisions) = drop_rain_on(Collider::solid(canopy(), 1., 0.));
+    expect_that!(collisions, eq(1));
+    expect_that!(slippery.y.to_untyped(), gt(0.));
+    expect_that!(slippery.x.to_untyped(), gt(5.));
+
+    let (sticky, collisions) = drop_rain_on(Collider::solid(canopy(), 1., 1.));
+    expect_that!(collisions, eq(1));
+    expect_that!(sticky.y.to_untyped(), gt(0.));
+    expect_that!(sticky.x.to_untyped().abs(), lt(1.));
+  }
+
+  #[gtest]
+  fn sensors_report_rain_without_deflecting_it() {
+    let square = Shape::Rect(WorldRect::new(WorldUnit::new(4.), WorldUnit::new(4.)));
+    let (vel, collisions) = drop_rain_on(Collider::sensor(square));
+    expect_that!(collisions, eq(1));
+    expect_that!(vel.y.to_untyped(), lt(0.));
+  }
+}
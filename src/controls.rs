@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use bevy::{
+  app::{App, Plugin},
+  ecs::system::{Query, Resource},
+  input::{
+    gamepad::{Gamepad, GamepadButton},
+    keyboard::KeyCode,
+    ButtonInput,
+  },
+  log::error,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Something the player can do, which may be bound to any keys and gamepad
+/// buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+  MoveUp,
+  MoveDown,
+  MoveLeft,
+  MoveRight,
+  Pause,
+  TiltLeft,
+  TiltRight,
+  /// Switches the mouse and touch controls between following the pointer and
+  /// dragging the umbrella.
+  Ability,
+  /// Moves on from the title, wave summary and game over screens.
+  Confirm,
+  /// Backs out of the title and game over screens.
+  Back,
+}
+
+impl Action {
+  pub const ALL: [Self; 10] = [
+    Self::MoveUp,
+    Self::MoveDown,
+    Self::MoveLeft,
+    Self::MoveRight,
+    Self::Pause,
+    Self::TiltLeft,
+    Self::TiltRight,
+    Self::Ability,
+    Self::Confirm,
+    Self::Back,
+  ];
+
+  pub fn label(&self) -> &'static str {
+    match self {
+      Self::MoveUp => "Move up",
+      Self::MoveDown => "Move down",
+      Self::MoveLeft => "Move left",
+      Self::MoveRight => "Move right",
+      Self::Pause => "Pause",
+      Self::TiltLeft => "Tilt left",
+      Self::TiltRight => "Tilt right",
+      Self::Ability => "Pointer mode",
+      Self::Confirm => "Confirm",
+      Self::Back => "Back",
+    }
+  }
+
+  /// Menu actions are only read outside of gameplay, so they can share keys
+  /// and buttons with gameplay actions.
+  fn is_menu(&self) -> bool {
+    matches!(self, Self::Confirm | Self::Back)
+  }
+
+  fn default_binding(&self) -> Binding {
+    let (keys, buttons) = match self {
+      Self::MoveUp => (
+        vec![KeyCode::KeyW, KeyCode::ArrowUp],
+        vec![GamepadButton::DPadUp],
+      ),
+      Self::MoveDown => (
+        vec![KeyCode::KeyS, KeyCode::ArrowDown],
+        vec![GamepadButton::DPadDown],
+      ),
+      Self::MoveLeft => (
+        vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+        vec![GamepadButton::DPadLeft],
+      ),
+      Self::MoveRight => (
+        vec![KeyCode::KeyD, KeyCode::ArrowRight],
+        vec![GamepadButton::DPadRight],
+      ),
+      Self::Pause => (
+        vec![KeyCode::Escape, KeyCode::KeyP],
+        vec![GamepadButton::Start],
+      ),
+      Self::TiltLeft => (
+        vec![KeyCode::KeyQ, KeyCode::Comma],
+        vec![GamepadButton::LeftTrigger],
+      ),
+      Self::TiltRight => (
+        vec![KeyCode::KeyE, KeyCode::Period],
+        vec![GamepadButton::RightTrigger],
+      ),
+      Self::Ability => (vec![KeyCode::KeyM], vec![]),
+      Self::Confirm => (
+        vec![KeyCode::Space, KeyCode::Enter],
+        vec![GamepadButton::Start],
+      ),
+      Self::Back => (vec![KeyCode::Escape], vec![GamepadButton::Select]),
+    };
+    Binding { keys, buttons }
+  }
+}
+
+/// The input device a binding belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Device {
+  Keyboard,
+  Gamepad,
+}
+
+/// One side of a keyboard shared by two players. Each player only responds to
+/// the keys bound on their side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyboardHalf {
+  Left,
+  Right,
+}
+
+impl KeyboardHalf {
+  /// Keys on the left hand side of a QWERTY keyboard. Every other key belongs
+  /// to the right half.
+  const LEFT_KEYS: [KeyCode; 28] = [
+    KeyCode::Escape,
+    KeyCode::Backquote,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Tab,
+    KeyCode::KeyQ,
+    KeyCode::KeyW,
+    KeyCode::KeyE,
+    KeyCode::KeyR,
+    KeyCode::KeyT,
+    KeyCode::CapsLock,
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::ShiftLeft,
+    KeyCode::KeyZ,
+    KeyCode::KeyX,
+    KeyCode::KeyC,
+    KeyCode::KeyV,
+    KeyCode::KeyB,
+    KeyCode::ControlLeft,
+    KeyCode::AltLeft,
+    KeyCode::Space,
+  ];
+
+  fn contains(&self, key: KeyCode) -> bool {
+    Self::LEFT_KEYS.contains(&key) == (*self == Self::Left)
+  }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Binding {
+  keys: Vec<KeyCode>,
+  buttons: Vec<GamepadButton>,
+}
+
+#[derive(Debug, Error)]
+pub enum ControlsError {
+  #[error("Could not access saved controls: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Could not parse saved controls: {0}")]
+  Parse(#[from] ron::de::SpannedError),
+  #[error("Could not serialize controls: {0}")]
+  Serialize(#[from] ron::Error),
+  #[cfg(target_arch = "wasm32")]
+  #[error("Browser storage is unavailable")]
+  NoStorage,
+}
+
+/// Maps every `Action` to the keys and gamepad buttons that trigger it.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Controls {
+  bindings: HashMap<Action, Binding>,
+}
+
+impl Default for Controls {
+  fn default() -> Self {
+    Self {
+      bindings: Action::ALL
+        .into_iter()
+        .map(|action| (action, action.default_binding()))
+        .collect(),
+    }
+  }
+}
+
+impl Controls {
+  #[cfg(not(target_arch = "wasm32"))]
+  const PATH: &str = "controls.ron";
+  #[cfg(target_arch = "wasm32")]
+  const STORAGE_KEY: &str = "rain_game.controls";
+
+  /// Every action has a binding, even if it is empty.
+  fn binding(&self, action: Action) -> &Binding {
+    &self.bindings[&action]
+  }
+
+  pub fn key_pressed(&self, action: Action, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+    keyboard_input.any_pressed(self.binding(action).keys.iter().copied())
+  }
+
+  /// Like `key_pressed`, but only for the keys bound on one side of the
+  /// keyboard.
+  pub fn key_pressed_on(
+    &self,
+    action: Action,
+    half: KeyboardHalf,
+    keyboard_input: &ButtonInput<KeyCode>,
+  ) -> bool {
+    self
+      .binding(action)
+      .keys
+      .iter()
+      .any(|&key| half.contains(key) && keyboard_input.pressed(key))
+  }
+
+  pub fn button_pressed(&self, action: Action, gamepad: &Gamepad) -> bool {
+    gamepad.any_pressed(self.binding(action).buttons.iter().copied())
+  }
+
+  /// True if any key or gamepad button bound to `action` was pressed this
+  /// frame.
+  pub fn just_pressed(
+    &self,
+    action: Action,
+    keyboard_input: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+  ) -> bool {
+    let binding = self.binding(action);
+    keyboard_input.any_just_pressed(binding.keys.iter().copied())
+      || gamepads.iter().any(|gamepad| {
+        binding
+          .buttons
+          .iter()
+          .any(|&button| gamepad.just_pressed(button))
+      })
+  }
+
+  /// A readable list of what `action` is bound to on `device`.
+  pub fn describe(&self, action: Action, device: Device) -> String {
+    let binding = self.binding(action);
+    let names: Vec<String> = match device {
+      Device::Keyboard => binding
+        .keys
+        .iter()
+        .map(|key| format!("{key:?}").trim_start_matches("Key").to_owned())
+        .collect(),
+      Device::Gamepad => binding
+        .buttons
+        .iter()
+        .map(|button| format!("{button:?}"))
+        .collect(),
+    };
+    if names.is_empty() {
+      "-".to_owned()
+    } else {
+      names.join(", ")
+    }
+  }
+
+  /// The bindings of the actions that are read alongside `action`.
+  fn bindings_alongside(&mut self, action: Action) -> impl Iterator<Item = &mut Binding> {
+    self
+      .bindings
+      .iter_mut()
+      .filter(move |(other, _)| other.is_menu() == action.is_menu())
+      .map(|(_, binding)| binding)
+  }
+
+  /// Binds `key` to `action` alone, removing it from any other action that is
+  /// read alongside it.
+  pub fn rebind_key(&mut self, action: Action, key: KeyCode) {
+    for binding in self.bindings_alongside(action) {
+      binding.keys.retain(|&bound| bound != key);
+    }
+    self.bindings.entry(action).or_default().keys = vec![key];
+  }
+
+  /// Binds `button` to `action` alone, removing it from any other action that
+  /// is read alongside it.
+  pub fn rebind_button(&mut self, action: Action, button: GamepadButton) {
+    for binding in self.bindings_alongside(action) {
+      binding.buttons.retain(|&bound| bound != button);
+    }
+    self.bindings.entry(action).or_default().buttons = vec![button];
+  }
+
+  fn to_ron(&self) -> Result<String, ControlsError> {
+    Ok(ron::ser::to_string_pretty(
+      self,
+      ron::ser::PrettyConfig::default(),
+    )?)
+  }
+
+  fn from_ron(ron: &str) -> Result<Self, ControlsError> {
+    let mut controls: Self = ron::from_str(ron)?;
+    // Actions added since the controls were saved get their defaults.
+    for action in Action::ALL {
+      controls
+        .bindings
+        .entry(action)
+        .or_insert_with(|| action.default_binding());
+    }
+    Ok(controls)
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  fn load() -> Result<Option<Self>, ControlsError> {
+    match std::fs::read_to_string(Self::PATH) {
+      Ok(ron) => Self::from_ron(&ron).map(Some),
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn save(&self) -> Result<(), ControlsError> {
+    std::fs::write(Self::PATH, self.to_ron()?)?;
+    Ok(())
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  fn local_storage() -> Result<web_sys::Storage, ControlsError> {
+    web_sys::window()
+      .and_then(|window| window.local_storage().ok().flatten())
+      .ok_or(ControlsError::NoStorage)
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  fn load() -> Result<Option<Self>, ControlsError> {
+    let ron = Self::local_storage()?
+      .get_item(Self::STORAGE_KEY)
+      .map_err(|_| ControlsError::NoStorage)?;
+    ron.map(|ron| Self::from_ron(&ron)).transpose()
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  pub fn save(&self) -> Result<(), ControlsError> {
+    Self::local_storage()?
+      .set_item(Self::STORAGE_KEY, &self.to_ron()?)
+      .map_err(|_| ControlsError::NoStorage)
+  }
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+  fn build(&self, app: &mut App) {
+    let controls = Controls::load().unwrap_or_else(|err| {
+      error!("{err}");
+      None
+    });
+    app.insert_resource(controls.unwrap_or_default());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::input::{keyboard::KeyCode, ButtonInput};
+  use googletest::prelude::*;
+
+  use super::{Action, Controls, Device, KeyboardHalf};
+
+  #[gtest]
+  fn rebinding_a_key_moves_it_between_actions() {
+    let mut controls = Controls::default();
+    controls.rebind_key(Action::MoveLeft, KeyCode::KeyW);
+
+    expect_that!(
+      controls.describe(Action::MoveLeft, Device::Keyboard),
+      eq("W")
+    );
+    expect_that!(
+      controls.describe(Action::MoveUp, Device::Keyboard),
+      eq("ArrowUp")
+    );
+  }
+
+  #[gtest]
+  fn rebinding_keeps_keys_shared_between_gameplay_and_menus() {
+    let mut controls = Controls::default();
+    controls.rebind_key(Action::Confirm, KeyCode::Escape);
+
+    expect_that!(
+      controls.describe(Action::Pause, Device::Keyboard),
+      eq("Escape, P")
+    );
+    expect_that!(controls.describe(Action::Back, Device::Keyboard), eq("-"));
+  }
+
+  #[gtest]
+  fn controls_survive_a_round_trip() {
+    let mut controls = Controls::default();
+    controls.rebind_key(Action::Ability, KeyCode::KeyJ);
+
+    let loaded = Controls::from_ron(&controls.to_ron().unwrap()).unwrap();
+    expect_that!(loaded.describe(Action::Ability, Device::Keyboard), eq("J"));
+    expect_that!(loaded.describe(Action::Pause, Device::Gamepad), eq("Start"));
+  }
+
+  #[gtest]
+  fn split_keyboard_halves_share_default_bindings() {
+    let controls = Controls::default();
+    let mut keyboard_input = ButtonInput::default();
+    keyboard_input.press(KeyCode::ArrowLeft);
+
+    expect_that!(
+      controls.key_pressed_on(Action::MoveLeft, KeyboardHalf::Right, &keyboard_input),
+      eq(true)
+    );
+    expect_that!(
+      controls.key_pressed_on(Action::MoveLeft, KeyboardHalf::Left, &keyboard_input),
+      eq(false)
+    );
+  }
+}
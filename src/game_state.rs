@@ -0,0 +1,195 @@
+use bevy::{
+  app::{App, AppExit, Plugin, Update},
+  asset::AssetServer,
+  ecs::{
+    bundle::Bundle,
+    event::EventWriter,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut},
+  },
+  hierarchy::BuildChildren,
+  input::{gamepad::Gamepad, keyboard::KeyCode, ButtonInput},
+  state::{
+    app::AppExtStates,
+    condition::in_state,
+    state::{ComputedStates, NextState, OnEnter, State, States},
+    state_scoped::StateScoped,
+  },
+  text::{JustifyText, TextFont, TextLayout},
+  ui::{widget::Text, AlignItems, JustifyContent, Node, PositionType, Val},
+  utils::default,
+};
+
+use crate::{
+  controls::{Action, Controls, Device},
+  lobby::LocalPlayers,
+  rng::GameRng,
+  score::ScoreResource,
+  settings::SettingsState,
+};
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+  #[default]
+  Title,
+  Playing,
+  Paused,
+  /// Between waves, showing how the last wave went.
+  WaveSummary,
+  GameOver,
+  /// Transient state used to restart a run without passing through the game
+  /// over screen. Leaving `InGame` for a frame clears out the previous run.
+  Restarting,
+}
+
+/// Computed state that exists while a run is in progress, paused or not.
+/// Gameplay entities are scoped to this state, so they are cleaned up
+/// whenever a run ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+  type SourceStates = GameState;
+
+  fn compute(sources: GameState) -> Option<Self> {
+    matches!(
+      sources,
+      GameState::Playing | GameState::Paused | GameState::WaveSummary
+    )
+    .then_some(Self)
+  }
+}
+
+#[derive(Bundle)]
+struct ScreenBundle {
+  node: Node,
+  scope: StateScoped<GameState>,
+}
+
+#[derive(Bundle)]
+struct ScreenTextBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+}
+
+pub struct GameStatePlugin;
+
+impl GameStatePlugin {
+  /// Run condition for gameplay systems in `FixedUpdate`. Once a tick queues
+  /// a state change, the remaining ticks of that frame are skipped, so a run
+  /// depends only on the number of ticks played and not on the frame rate.
+  pub fn playing(state: Res<State<GameState>>, next_state: Res<NextState<GameState>>) -> bool {
+    *state.get() == GameState::Playing && matches!(*next_state, NextState::Unchanged)
+  }
+
+  /// Spawns a full-screen text overlay that is removed when leaving `state`.
+  pub fn spawn_screen(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    state: GameState,
+    text: String,
+  ) {
+    commands
+      .spawn(ScreenBundle {
+        node: Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          height: Val::Percent(100.),
+          align_items: AlignItems::Center,
+          justify_content: JustifyContent::Center,
+          ..default()
+        },
+        scope: StateScoped(state),
+      })
+      .with_child(ScreenTextBundle {
+        text: Text::new(text),
+        font: TextFont {
+          font: asset_server.load("fonts/Rubik-VariableFont_wght.ttf"),
+          font_size: 67.0,
+          ..default()
+        },
+        layout: TextLayout::new_with_justify(JustifyText::Center),
+      });
+  }
+
+  fn show_title(mut commands: Commands, asset_server: Res<AssetServer>, controls: Res<Controls>) {
+    Self::spawn_screen(
+      &mut commands,
+      &asset_server,
+      GameState::Title,
+      format!(
+        "Rain Game\nPress {} to start\nPress C for controls",
+        controls.describe(Action::Confirm, Device::Keyboard)
+      ),
+    );
+  }
+
+  fn show_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<ScoreResource>,
+    players: Res<LocalPlayers>,
+    rng: Res<GameRng>,
+    controls: Res<Controls>,
+  ) {
+    let breakdown = score
+      .player_breakdown(players.count())
+      .map_or_else(String::new, |breakdown| format!("\n{breakdown}"));
+    Self::spawn_screen(
+      &mut commands,
+      &asset_server,
+      GameState::GameOver,
+      format!(
+        "Game Over\nScore: {}{breakdown}\nSeed: {}\nPress {} to restart",
+        score.points,
+        rng.seed(),
+        controls.describe(Action::Confirm, Device::Keyboard)
+      ),
+    );
+  }
+
+  fn handle_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    controls: Res<Controls>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+  ) {
+    let confirm = controls.just_pressed(Action::Confirm, &keyboard_input, &gamepads);
+    let back = controls.just_pressed(Action::Back, &keyboard_input, &gamepads);
+
+    match state.get() {
+      GameState::Title if confirm => next_state.set(GameState::Playing),
+      GameState::Title if back => {
+        exit.send(AppExit::Success);
+      }
+      GameState::WaveSummary if confirm => next_state.set(GameState::Playing),
+      GameState::GameOver if confirm => next_state.set(GameState::Playing),
+      GameState::GameOver if back => next_state.set(GameState::Title),
+      _ => {}
+    }
+  }
+
+  fn restart(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+  }
+}
+
+impl Plugin for GameStatePlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_state::<GameState>()
+      .add_computed_state::<InGame>()
+      .enable_state_scoped_entities::<GameState>()
+      .enable_state_scoped_entities::<InGame>()
+      .add_systems(OnEnter(GameState::Title), Self::show_title)
+      .add_systems(OnEnter(GameState::GameOver), Self::show_game_over)
+      .add_systems(OnEnter(GameState::Restarting), Self::restart)
+      .add_systems(
+        Update,
+        Self::handle_input.run_if(in_state(SettingsState::Closed)),
+      );
+  }
+}
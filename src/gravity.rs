@@ -2,29 +2,42 @@ use bevy::{
   app::{App, FixedUpdate, Plugin},
   ecs::{
     component::Component,
-    query::With,
     schedule::IntoSystemConfigs,
     system::{Query, Res},
   },
   math::Vec2,
   time::Time,
 };
-use bevy_world_space::world_unit::WorldUnit;
 
-use crate::movable::{MoveComponent, MovePlugin};
+use crate::{
+  game_state::GameStatePlugin,
+  movable::{MoveComponent, MovePlugin},
+  wave::Wave,
+};
+
+#[derive(Component)]
+pub struct GravityComponent {
+  /// Multiplies the wave's gravity.
+  pub scale: f32,
+}
 
-#[derive(Component, Default)]
-pub struct GravityComponent;
+impl Default for GravityComponent {
+  fn default() -> Self {
+    Self { scale: 1. }
+  }
+}
 
 pub struct GravityPlugin;
 
 impl GravityPlugin {
-  const G: WorldUnit = WorldUnit::new(16.0);
-
-  fn apply_gravity(time: Res<Time>, mut query: Query<&mut MoveComponent, With<GravityComponent>>) {
-    let g = Self::G * time.delta_secs();
-    for mut move_component in &mut query {
-      move_component.delta += -g * Vec2::Y;
+  pub fn apply_gravity(
+    time: Res<Time>,
+    wave: Res<Wave>,
+    mut query: Query<(&mut MoveComponent, &GravityComponent)>,
+  ) {
+    let g = wave.settings().gravity * time.delta_secs();
+    for (mut move_component, gravity) in &mut query {
+      move_component.delta += -g * gravity.scale * Vec2::Y;
     }
   }
 }
@@ -33,7 +46,9 @@ impl Plugin for GravityPlugin {
   fn build(&self, app: &mut App) {
     app.add_systems(
       FixedUpdate,
-      Self::apply_gravity.before(MovePlugin::apply_moves),
+      Self::apply_gravity
+        .run_if(GameStatePlugin::playing)
+        .before(MovePlugin::apply_moves),
     );
   }
 }
@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  color::{Alpha, Color},
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::{Event, EventWriter},
+    query::Without,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut},
+  },
+  math::Vec2,
+  sprite::Sprite,
+  state::state_scoped::StateScoped,
+  time::{Time, Timer, TimerMode},
+};
+use bevy_world_space::{
+  position::Position,
+  world_unit::{WorldUnit, WorldVec2},
+};
+
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  movable::MovePlugin,
+  npc::NpcPlugin,
+  player::Player,
+  rng::{DrawRandom, GameRng},
+  wave::Wave,
+};
+
+/// Sent when lightning reaches the ground without hitting an umbrella on the
+/// way down.
+#[derive(Event)]
+pub struct LightningStrike {
+  pub x: WorldUnit,
+  pub half_width: WorldUnit,
+}
+
+impl LightningStrike {
+  pub fn covers(&self, x: WorldUnit) -> bool {
+    (x - self.x).abs() < self.half_width
+  }
+}
+
+/// Marks the ground where lightning is about to strike.
+#[derive(Component)]
+struct LightningWarning {
+  timer: Timer,
+}
+
+#[derive(Bundle)]
+struct LightningWarningBundle {
+  sprite: Sprite,
+  pos: Position,
+  warning: LightningWarning,
+  scope: StateScoped<InGame>,
+}
+
+impl LightningWarningBundle {
+  const IMG_WIDTH: u32 = 100;
+  const IMG_HEIGHT: u32 = 10;
+
+  const COLOR: Color = Color::srgba(1., 0.9, 0.2, 0.6);
+
+  const WIDTH: WorldUnit = WorldUnit::new(6.);
+
+  /// How long the warning shows before the lightning strikes.
+  const DURATION: Duration = Duration::from_secs(1);
+
+  /// How many times the warning blinks each second.
+  const BLINK_RATE: f32 = 6.;
+
+  const Z_IDX: f32 = 0.9;
+
+  fn new(x: WorldUnit) -> Self {
+    Self {
+      sprite: Sprite::from_color(
+        Self::COLOR,
+        Vec2::new(Self::IMG_WIDTH as f32, Self::IMG_HEIGHT as f32),
+      ),
+      pos: Position::new(
+        WorldVec2::new(x, WorldVec2::new_normalized(0., NpcPlugin::GROUND_Y).y),
+        Self::WIDTH,
+        Self::IMG_WIDTH,
+        Self::Z_IDX,
+      ),
+      warning: LightningWarning {
+        timer: Timer::new(Self::DURATION, TimerMode::Once),
+      },
+      scope: StateScoped(InGame),
+    }
+  }
+}
+
+/// Lights up the whole screen for a moment when lightning strikes.
+#[derive(Component)]
+struct Flash {
+  timer: Timer,
+}
+
+#[derive(Bundle)]
+struct FlashBundle {
+  sprite: Sprite,
+  pos: Position,
+  flash: Flash,
+  scope: StateScoped<InGame>,
+}
+
+impl FlashBundle {
+  const IMG_WIDTH: u32 = 100;
+
+  const DURATION: Duration = Duration::from_millis(200);
+
+  const Z_IDX: f32 = 10.;
+
+  fn new() -> Self {
+    let aspect_ratio = (WorldUnit::TOP / WorldUnit::RIGHT.to_untyped()).to_untyped();
+    Self {
+      sprite: Sprite::from_color(
+        Color::WHITE,
+        Vec2::new(
+          Self::IMG_WIDTH as f32,
+          Self::IMG_WIDTH as f32 * aspect_ratio,
+        ),
+      ),
+      pos: Position::new(
+        WorldVec2::ZERO,
+        WorldUnit::RIGHT * 2.,
+        Self::IMG_WIDTH,
+        Self::Z_IDX,
+      ),
+      flash: Flash {
+        timer: Timer::new(Self::DURATION, TimerMode::Once),
+      },
+      scope: StateScoped(InGame),
+    }
+  }
+}
+
+/// Storms now and then send down lightning, after warning where it will
+/// strike. An umbrella in the way takes the hit, sheltering the NPCs below.
+pub struct LightningPlugin;
+
+impl LightningPlugin {
+  /// Lightning never strikes right at the edge of the screen, where NPCs
+  /// haven't fully entered yet.
+  const MAX_X: f32 = 0.85;
+
+  /// How far an umbrella is thrown down when lightning hits it.
+  const KNOCKBACK: WorldUnit = WorldUnit::new(6.);
+
+  fn brew_storm(
+    mut commands: Commands,
+    time: Res<Time>,
+    wave: Res<Wave>,
+    mut rng: ResMut<GameRng>,
+  ) {
+    if rng.f32() < wave.settings().lightning_rate * time.delta_secs() {
+      let x = WorldUnit::RIGHT * ((rng.f32() * 2. - 1.) * Self::MAX_X);
+      commands.spawn(LightningWarningBundle::new(x));
+    }
+  }
+
+  fn strike(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut strikes: EventWriter<LightningStrike>,
+    mut warnings: Query<(Entity, &mut LightningWarning, &mut Sprite, &Position)>,
+    mut umbrellas: Query<(&mut Position, &Player), Without<LightningWarning>>,
+  ) {
+    for (entity, mut warning, mut sprite, warning_pos) in &mut warnings {
+      warning.timer.tick(time.delta());
+      if !warning.timer.finished() {
+        let blink_on =
+          (warning.timer.elapsed_secs() * LightningWarningBundle::BLINK_RATE).fract() < 0.5;
+        sprite.color = LightningWarningBundle::COLOR.with_alpha(if blink_on { 0.6 } else { 0.2 });
+        continue;
+      }
+
+      commands.entity(entity).despawn();
+      commands.spawn(FlashBundle::new());
+      let strike = LightningStrike {
+        x: warning_pos.pos.x,
+        half_width: LightningWarningBundle::WIDTH / 2.,
+      };
+      let mut blocked = false;
+      for (mut umbrella_pos, player) in &mut umbrellas {
+        // An umbrella down at the ground doesn't shelter anything.
+        let above_ground = umbrella_pos.pos.y > warning_pos.pos.y;
+        let reach = strike.half_width + player.width() / 2.;
+        if above_ground && (umbrella_pos.pos.x - strike.x).abs() < reach {
+          umbrella_pos.pos.y -= Self::KNOCKBACK;
+          blocked = true;
+        }
+      }
+      if !blocked {
+        strikes.send(strike);
+      }
+    }
+  }
+
+  fn fade_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Flash, &mut Sprite)>,
+  ) {
+    for (entity, mut flash, mut sprite) in &mut query {
+      flash.timer.tick(time.delta());
+      if flash.timer.finished() {
+        commands.entity(entity).despawn();
+      } else {
+        sprite.color = Color::WHITE.with_alpha(flash.timer.fraction_remaining());
+      }
+    }
+  }
+}
+
+impl Plugin for LightningPlugin {
+  fn build(&self, app: &mut App) {
+    app.add_event::<LightningStrike>().add_systems(
+      FixedUpdate,
+      (
+        Self::brew_storm.in_set(DrawRandom::Lightning),
+        Self::strike.before(MovePlugin::apply_moves),
+        Self::fade_flashes,
+      )
+        .chain()
+        .run_if(GameStatePlugin::playing),
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::ecs::query::With;
+  use bevy_world_space::{
+    position::Position,
+    world_unit::{WorldUnit, WorldVec2},
+  };
+  use googletest::prelude::*;
+
+  use crate::{npc::NpcPlugin, player::Player, test_harness::TestApp};
+
+  use super::{LightningStrike, LightningWarningBundle};
+
+  fn strike_at(app: &mut TestApp, x: f32) {
+    app
+      .world_mut()
+      .spawn(LightningWarningBundle::new(WorldUnit::new(x)));
+    app.run_for(LightningWarningBundle::DURATION);
+  }
+
+  #[gtest]
+  fn umbrellas_take_the_hit_for_the_ground_below() {
+    let mut app = TestApp::new();
+    app.start_playing().track_events::<LightningStrike>();
+    let umbrella_y = |app: &mut TestApp| {
+      let world = app.world_mut();
+      let pos = world
+        .query_filtered::<&Position, With<Player>>()
+        .single(world);
+      pos.pos.y.to_untyped()
+    };
+    let start = umbrella_y(&mut app);
+
+    strike_at(&mut app, 0.);
+    expect_that!(app.event_count::<LightningStrike>(), eq(0));
+    expect_that!(umbrella_y(&mut app), lt(start));
+
+    strike_at(&mut app, 25.);
+    expect_that!(app.event_count::<LightningStrike>(), eq(1));
+  }
+
+  #[gtest]
+  fn umbrellas_on_the_ground_dont_block_lightning() {
+    let mut app = TestApp::new();
+    app.start_playing().track_events::<LightningStrike>();
+    let world = app.world_mut();
+    let mut pos = world
+      .query_filtered::<&mut Position, With<Player>>()
+      .single_mut(world);
+    pos.pos = WorldVec2::new_normalized(0., NpcPlugin::GROUND_Y - 0.01);
+
+    strike_at(&mut app, 0.);
+    expect_that!(app.event_count::<LightningStrike>(), eq(1));
+  }
+}
@@ -0,0 +1,114 @@
+use bevy::{
+  app::{App, FixedUpdate, Plugin, Startup, Update},
+  asset::AssetServer,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    event::{Event, EventReader},
+    query::With,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Res, ResMut, Resource, Single},
+  },
+  state::state::{NextState, OnEnter},
+  text::{JustifyText, TextFont, TextLayout},
+  ui::{widget::Text, Node, PositionType, Val},
+  utils::default,
+};
+
+use bevy_world_space::world_init::WorldInitPlugin;
+
+use crate::game_state::{GameState, GameStatePlugin, InGame};
+
+/// Sent whenever an NPC gets soaked by the rain.
+#[derive(Event)]
+pub struct LoseLife;
+
+#[derive(Resource)]
+struct LivesResource {
+  remaining: u32,
+}
+
+impl Default for LivesResource {
+  fn default() -> Self {
+    Self { remaining: LivesPlugin::STARTING_LIVES }
+  }
+}
+
+#[derive(Component)]
+struct Lives;
+
+#[derive(Bundle)]
+pub struct LivesBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+  node: Node,
+  lives: Lives,
+}
+
+pub struct LivesPlugin;
+
+impl LivesPlugin {
+  const STARTING_LIVES: u32 = 3;
+
+  fn initialize_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(LivesBundle {
+      text: Text::new(format!("Lives: {}", Self::STARTING_LIVES)),
+      font: TextFont {
+        font: asset_server.load("fonts/Rubik-VariableFont_wght.ttf"),
+        font_size: 67.0,
+        ..default()
+      },
+      layout: TextLayout::new_with_justify(JustifyText::Center),
+      node: Node {
+        position_type: PositionType::Absolute,
+        top: Val::Px(5.0),
+        left: Val::Px(5.0),
+        ..default()
+      },
+      lives: Lives,
+    });
+  }
+
+  fn lose_lives(
+    mut lost: EventReader<LoseLife>,
+    mut lives: ResMut<LivesResource>,
+    mut next_state: ResMut<NextState<GameState>>,
+  ) {
+    let lost = lost.read().count() as u32;
+    if lost == 0 {
+      return;
+    }
+
+    lives.remaining = lives.remaining.saturating_sub(lost);
+    if lives.remaining == 0 {
+      next_state.set(GameState::GameOver);
+    }
+  }
+
+  fn reset_lives(mut lives: ResMut<LivesResource>) {
+    *lives = LivesResource::default();
+  }
+
+  fn update_lives(mut lives_ui: Single<&mut Text, With<Lives>>, lives: Res<LivesResource>) {
+    lives_ui.0 = format!("Lives: {}", lives.remaining);
+  }
+}
+
+impl Plugin for LivesPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<LoseLife>()
+      .insert_resource(LivesResource::default())
+      .add_systems(
+        Startup,
+        LivesPlugin::initialize_ui.after(WorldInitPlugin::world_init),
+      )
+      .add_systems(
+        FixedUpdate,
+        LivesPlugin::lose_lives.run_if(GameStatePlugin::playing),
+      )
+      .add_systems(OnEnter(InGame), LivesPlugin::reset_lives)
+      .add_systems(Update, LivesPlugin::update_lives);
+  }
+}
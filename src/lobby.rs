@@ -0,0 +1,281 @@
+use bevy::{
+  app::{App, Plugin, Update},
+  asset::AssetServer,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    query::With,
+    schedule::{common_conditions::resource_changed, IntoSystemConfigs},
+    system::{Commands, Query, Res, ResMut, Resource, Single},
+  },
+  input::{
+    gamepad::{Gamepad, GamepadButton},
+    keyboard::KeyCode,
+    ButtonInput,
+  },
+  state::{condition::in_state, state::OnEnter, state_scoped::StateScoped},
+  text::{JustifyText, TextFont, TextLayout},
+  ui::{widget::Text, Node, PositionType, Val},
+  utils::default,
+};
+
+use crate::{
+  controls::KeyboardHalf, game_state::GameState, player::PlayerId, settings::SettingsState,
+};
+
+/// What a local player steers their umbrella with.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputDevice {
+  /// The whole keyboard.
+  Keyboard,
+  /// One side of a keyboard shared with another player.
+  SplitKeyboard(KeyboardHalf),
+  Gamepad(Entity),
+}
+
+impl InputDevice {
+  fn label(&self) -> &'static str {
+    match self {
+      Self::Keyboard => "Keyboard",
+      Self::SplitKeyboard(KeyboardHalf::Left) => "Keyboard (left)",
+      Self::SplitKeyboard(KeyboardHalf::Right) => "Keyboard (right)",
+      Self::Gamepad(_) => "Gamepad",
+    }
+  }
+}
+
+/// The players sharing the screen, in order. The first player always plays on
+/// the keyboard, and also gets the pointer and any gamepad that hasn't joined
+/// as a player of its own.
+#[derive(Resource)]
+pub struct LocalPlayers {
+  devices: Vec<InputDevice>,
+}
+
+impl Default for LocalPlayers {
+  fn default() -> Self {
+    Self { devices: vec![InputDevice::Keyboard] }
+  }
+}
+
+impl LocalPlayers {
+  pub const MAX: usize = 4;
+
+  /// Players whose input comes from a recording rather than any device.
+  pub fn replaying(count: usize) -> Self {
+    Self {
+      devices: vec![InputDevice::Keyboard; count],
+    }
+  }
+
+  pub fn devices(&self) -> &[InputDevice] {
+    &self.devices
+  }
+
+  pub fn count(&self) -> usize {
+    self.devices.len()
+  }
+
+  fn join(&mut self, device: InputDevice) {
+    if !self.devices.contains(&device) && self.count() < Self::MAX {
+      self.devices.push(device);
+    }
+  }
+
+  fn leave(&mut self, device: InputDevice) {
+    self.devices.retain(|&joined| joined != device);
+  }
+
+  /// Gives the right half of the keyboard to a new player, or hands it back
+  /// to the first player.
+  fn toggle_split_keyboard(&mut self) {
+    let right = InputDevice::SplitKeyboard(KeyboardHalf::Right);
+    if self.devices.contains(&right) {
+      self.leave(right);
+      self.devices[0] = InputDevice::Keyboard;
+    } else if self.count() < Self::MAX {
+      self.devices[0] = InputDevice::SplitKeyboard(KeyboardHalf::Left);
+      self.devices.push(right);
+    }
+  }
+
+  fn describe(&self) -> String {
+    let players: Vec<String> = self
+      .devices
+      .iter()
+      .enumerate()
+      .map(|(id, device)| format!("{}: {}", PlayerId(id).label(), device.label()))
+      .collect();
+    format!(
+      "{}\nGamepads: {:?} to join, {:?} to leave\nTab to share the keyboard",
+      players.join("   "),
+      LobbyPlugin::JOIN_BUTTON,
+      LobbyPlugin::LEAVE_BUTTON
+    )
+  }
+}
+
+#[derive(Component)]
+struct LobbyText;
+
+#[derive(Bundle)]
+struct LobbyTextBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+  node: Node,
+  lobby_text: LobbyText,
+  scope: StateScoped<GameState>,
+}
+
+/// Lets up to `LocalPlayers::MAX` players join from the title screen.
+pub struct LobbyPlugin;
+
+impl LobbyPlugin {
+  const JOIN_BUTTON: GamepadButton = GamepadButton::South;
+  const LEAVE_BUTTON: GamepadButton = GamepadButton::East;
+  const SPLIT_KEYBOARD_KEY: KeyCode = KeyCode::Tab;
+
+  fn show_lobby(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    players: Res<LocalPlayers>,
+  ) {
+    commands.spawn(LobbyTextBundle {
+      text: Text::new(players.describe()),
+      font: TextFont {
+        font: asset_server.load("fonts/Rubik-VariableFont_wght.ttf"),
+        font_size: 33.0,
+        ..default()
+      },
+      layout: TextLayout::new_with_justify(JustifyText::Center),
+      node: Node {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(20.0),
+        width: Val::Percent(100.),
+        ..default()
+      },
+      lobby_text: LobbyText,
+      scope: StateScoped(GameState::Title),
+    });
+  }
+
+  fn manage_players(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut players: ResMut<LocalPlayers>,
+  ) {
+    let connected = |device: &InputDevice| match device {
+      InputDevice::Gamepad(gamepad) => gamepads.contains(*gamepad),
+      _ => true,
+    };
+    if !players.devices.iter().all(connected) {
+      players.devices.retain(connected);
+    }
+
+    if keyboard_input.just_pressed(Self::SPLIT_KEYBOARD_KEY) {
+      players.toggle_split_keyboard();
+    }
+    for (entity, gamepad) in &gamepads {
+      if gamepad.just_pressed(Self::JOIN_BUTTON) {
+        players.join(InputDevice::Gamepad(entity));
+      } else if gamepad.just_pressed(Self::LEAVE_BUTTON) {
+        players.leave(InputDevice::Gamepad(entity));
+      }
+    }
+  }
+
+  fn update_lobby(mut lobby_text: Single<&mut Text, With<LobbyText>>, players: Res<LocalPlayers>) {
+    lobby_text.0 = players.describe();
+  }
+}
+
+impl Plugin for LobbyPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<LocalPlayers>()
+      .add_systems(OnEnter(GameState::Title), Self::show_lobby)
+      .add_systems(
+        Update,
+        (
+          Self::manage_players.run_if(in_state(SettingsState::Closed)),
+          Self::update_lobby.run_if(resource_changed::<LocalPlayers>),
+        )
+          .chain()
+          .run_if(in_state(GameState::Title)),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::{
+    ecs::entity::Entity,
+    input::{keyboard::KeyCode, ButtonInput},
+  };
+  use bevy_world_space::position::Position;
+  use googletest::prelude::*;
+
+  use crate::{controls::KeyboardHalf, player::PlayerId, test_harness::TestApp};
+
+  use super::{InputDevice, LocalPlayers};
+
+  #[gtest]
+  fn splitting_the_keyboard_adds_a_player() {
+    let mut players = LocalPlayers::default();
+    players.toggle_split_keyboard();
+    expect_that!(
+      players.devices(),
+      elements_are![
+        eq(&InputDevice::SplitKeyboard(KeyboardHalf::Left)),
+        eq(&InputDevice::SplitKeyboard(KeyboardHalf::Right))
+      ]
+    );
+
+    players.toggle_split_keyboard();
+    expect_that!(players.devices(), elements_are![eq(&InputDevice::Keyboard)]);
+  }
+
+  #[gtest]
+  fn no_more_than_four_players_can_join() {
+    let mut players = LocalPlayers::default();
+    for idx in 0..5 {
+      players.join(InputDevice::Gamepad(Entity::from_raw(idx)));
+    }
+    players.join(InputDevice::Gamepad(Entity::from_raw(0)));
+
+    expect_that!(players.count(), eq(LocalPlayers::MAX));
+  }
+
+  #[gtest]
+  fn split_keyboard_players_steer_their_own_umbrellas() {
+    let mut app = TestApp::new();
+    app
+      .world_mut()
+      .resource_mut::<LocalPlayers>()
+      .toggle_split_keyboard();
+    app.step().start_playing();
+
+    let umbrella_x = |app: &mut TestApp| {
+      let world = app.world_mut();
+      let mut x = [0.; 2];
+      for (id, pos) in world.query::<(&PlayerId, &Position)>().iter(world) {
+        x[id.0] = pos.pos.x.to_untyped();
+      }
+      x
+    };
+    let start = umbrella_x(&mut app);
+
+    app
+      .world_mut()
+      .resource_mut::<ButtonInput<KeyCode>>()
+      .press(KeyCode::ArrowRight);
+    app.run_for(Duration::from_millis(200));
+
+    let end = umbrella_x(&mut app);
+    expect_that!(end[1] - start[1], gt(end[0] - start[0] + 1.));
+  }
+}
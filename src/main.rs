@@ -1,15 +1,39 @@
 #![allow(clippy::type_complexity)]
 
+mod apple;
 mod background;
+#[cfg(test)]
+mod benches;
+mod broadphase;
+mod collider;
+mod controls;
 mod framerate;
+mod game_state;
 mod gravity;
+mod lightning;
+mod lives;
+mod lobby;
 mod movable;
 mod npc;
+mod pause;
 mod player;
+mod pointer;
+mod puddle;
 mod rain;
+mod rain_pattern;
+mod replay;
+mod rng;
+mod roster;
 mod score;
+mod settings;
 mod shack;
+mod splash;
+#[cfg(test)]
+mod test_harness;
+mod wave;
+mod wind;
 
+use apple::ApplePlugin;
 use background::BackgroundPlugin;
 use bevy::{
   app::App,
@@ -20,14 +44,29 @@ use bevy::{
   DefaultPlugins,
 };
 use bevy_world_space::WorldSpacePlugins;
+use collider::CollisionPlugin;
+use controls::ControlsPlugin;
 use framerate::FrameratePlugin;
+use game_state::GameStatePlugin;
 use gravity::GravityPlugin;
+use lightning::LightningPlugin;
+use lives::LivesPlugin;
+use lobby::LobbyPlugin;
 use movable::MovePlugin;
 use npc::NpcPlugin;
+use pause::PausePlugin;
 use player::PlayerPlugin;
+use pointer::PointerPlugin;
+use puddle::PuddlePlugin;
 use rain::RainPlugin;
+use replay::ReplayPlugin;
+use rng::RngPlugin;
 use score::ScorePlugin;
+use settings::SettingsPlugin;
 use shack::ShackPlugin;
+use splash::SplashPlugin;
+use wave::WavePlugin;
+use wind::WindPlugin;
 
 fn main() {
   App::new()
@@ -49,15 +88,28 @@ fn main() {
         }),
     )
     .add_plugins(WorldSpacePlugins)
-    .add_plugins(FrameratePlugin)
+    .add_plugins((FrameratePlugin, RngPlugin, ReplayPlugin))
+    .add_plugins((ControlsPlugin, SettingsPlugin, LobbyPlugin))
     .add_plugins((
       PlayerPlugin,
+      PointerPlugin,
       RainPlugin,
+      SplashPlugin,
+      PuddlePlugin,
       NpcPlugin,
       ShackPlugin,
       BackgroundPlugin,
+      ApplePlugin,
+    ))
+    .add_plugins((MovePlugin, GravityPlugin, CollisionPlugin))
+    .add_plugins((
+      ScorePlugin,
+      LivesPlugin,
+      GameStatePlugin,
+      PausePlugin,
+      WavePlugin,
+      WindPlugin,
+      LightningPlugin,
     ))
-    .add_plugins((MovePlugin, GravityPlugin))
-    .add_plugins(ScorePlugin)
     .run();
 }
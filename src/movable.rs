@@ -2,6 +2,7 @@ use bevy::{
   app::{App, FixedUpdate, Plugin},
   ecs::{
     component::Component,
+    schedule::IntoSystemConfigs,
     system::{Query, Res},
   },
   time::Time,
@@ -9,6 +10,8 @@ use bevy::{
 
 use bevy_world_space::{position::Position, world_unit::WorldVec2};
 
+use crate::game_state::GameStatePlugin;
+
 #[derive(Component, Default)]
 pub struct MoveComponent {
   pub delta: WorldVec2,
@@ -28,6 +31,9 @@ impl MovePlugin {
 
 impl Plugin for MovePlugin {
   fn build(&self, app: &mut App) {
-    app.add_systems(FixedUpdate, MovePlugin::apply_moves);
+    app.add_systems(
+      FixedUpdate,
+      MovePlugin::apply_moves.run_if(GameStatePlugin::playing),
+    );
   }
 }
@@ -1,13 +1,15 @@
 use std::time::Duration;
 
+#[cfg(test)]
+use bevy::ecs::world::World;
 use bevy::{
-  app::{App, FixedUpdate, Plugin, Startup, Update},
-  asset::{AssetServer, Handle},
+  app::{App, FixedUpdate, Plugin, Startup},
+  asset::{AssetApp, AssetServer, Assets, Handle},
   ecs::{
     bundle::Bundle,
     component::Component,
     entity::Entity,
-    event::EventWriter,
+    event::{EventReader, EventWriter},
     query::With,
     schedule::IntoSystemConfigs,
     system::{Commands, Query, Res, ResMut, Resource},
@@ -15,6 +17,7 @@ use bevy::{
   image::Image,
   math::Vec2,
   sprite::Sprite,
+  state::{state::OnEnter, state_scoped::StateScoped},
   time::{Time, Timer, TimerMode},
 };
 use bevy_world_space::{
@@ -24,48 +27,21 @@ use bevy_world_space::{
 };
 
 use crate::{
+  collider::{Collider, CollisionPlugin, RainCollision, Shape},
+  game_state::{GameStatePlugin, InGame},
+  lightning::LightningStrike,
+  lives::LoseLife,
   movable::MoveComponent,
-  rain::{Rain, RainBundle},
+  player::{Player, PlayerId},
+  puddle::Puddle,
+  rain::{InPlay, Rain, RainPool},
+  rng::{DrawRandom, GameRng, RngPlugin},
+  roster::{Character, Roster, RosterLoader},
   score::EarnPoint,
+  splash::Splash,
+  wave::{Wave, WaveSettings},
 };
 
-enum Character {
-  Boy,
-  Nun,
-  OldMan,
-  SchoolGirl,
-  Baker,
-  BearGirl,
-  BoyCap,
-}
-
-impl Character {
-  const fn num_states(&self, npc_assets: &NpcAssets) -> usize {
-    match self {
-      Self::Boy => npc_assets.boy_sprites.len(),
-      Self::Nun => 1,
-      Self::OldMan => npc_assets.old_man_sprites.len(),
-      Self::SchoolGirl => npc_assets.school_girl_sprites.len(),
-      Self::Baker => npc_assets.baker_sprites.len(),
-      Self::BearGirl => npc_assets.bear_girl_sprites.len(),
-      Self::BoyCap => npc_assets.boy_cap_sprites.len(),
-    }
-  }
-
-  fn random_character() -> Self {
-    match fastrand::i32(0..7) {
-      0 => Self::Boy,
-      1 => Self::Nun,
-      2 => Self::OldMan,
-      3 => Self::SchoolGirl,
-      4 => Self::Baker,
-      5 => Self::BearGirl,
-      6 => Self::BoyCap,
-      _ => unreachable!(),
-    }
-  }
-}
-
 #[derive(Default)]
 enum State {
   #[default]
@@ -78,14 +54,16 @@ enum State {
 impl State {
   const ANGRY_DURATION: Duration = Duration::from_secs(2);
 
-  fn absorb_rain(&mut self) {
+  /// Soaks the NPC, returning true if it was dry before absorbing the rain.
+  fn absorb_rain(&mut self) -> bool {
     match self {
       Self::Dry => {
         *self = Self::Wet {
           timer: Timer::new(Self::ANGRY_DURATION, TimerMode::Once),
-        }
+        };
+        true
       }
-      Self::Wet { .. } => {}
+      Self::Wet { .. } => false,
     }
   }
 
@@ -111,61 +89,126 @@ impl State {
   }
 }
 
+/// The direction an NPC walks in. NPCs enter from the opposite edge of the
+/// screen and must reach the edge they are facing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Facing {
+  Left,
+  Right,
+}
+
+impl Facing {
+  fn random(rng: &mut fastrand::Rng) -> Self {
+    if rng.bool() {
+      Self::Left
+    } else {
+      Self::Right
+    }
+  }
+
+  fn direction(&self) -> Vec2 {
+    match self {
+      Self::Left => Vec2::NEG_X,
+      Self::Right => Vec2::X,
+    }
+  }
+}
+
 #[derive(Component)]
 #[require(MoveComponent)]
-struct Npc {
-  character: Character,
+pub struct Npc {
+  character: usize,
+  facing: Facing,
+  walk_speed: WorldUnit,
+  width: WorldUnit,
+  height: WorldUnit,
   state: State,
+  /// The last player whose umbrella was over the NPC, who gets the point if
+  /// it makes it home.
+  sheltered_by: Option<PlayerId>,
+  /// Set while the NPC is stunned by lightning and can't walk.
+  stun: Option<Timer>,
   animation_idx: usize,
   timer: Timer,
 }
 
 impl Npc {
-  const WALK_SPEED: WorldUnit = WorldUnit::new(2.5);
-  const ANIMATION_PERIOD: Duration = Duration::from_millis(250);
-
-  fn new(character: Character) -> Self {
+  fn new(character_idx: usize, character: &Character, facing: Facing, speed_scale: f32) -> Self {
     Self {
-      character,
+      character: character_idx,
+      facing,
+      walk_speed: character.walk_speed * speed_scale,
+      width: character.width,
+      height: character.height,
       state: State::Dry,
+      sheltered_by: None,
+      stun: None,
       animation_idx: 0,
-      timer: Timer::new(Self::ANIMATION_PERIOD, TimerMode::Repeating),
+      timer: Timer::new(character.animation_period, TimerMode::Repeating),
+    }
+  }
+
+  fn bounding_rect(&self) -> WorldRect {
+    WorldRect::new(self.width, self.height)
+  }
+
+  /// True once the NPC has fully entered the screen.
+  fn has_entered(&self, pos: WorldVec2) -> bool {
+    match self.facing {
+      Facing::Left => pos.x < WorldUnit::RIGHT - self.width / 2.,
+      Facing::Right => pos.x > WorldUnit::LEFT + self.width / 2.,
     }
   }
 
-  fn current_asset(&self, npc_assets: &NpcAssets) -> Handle<Image> {
+  /// True once the NPC has reached the edge of the screen it is walking
+  /// towards.
+  fn has_arrived(&self, pos: WorldVec2) -> bool {
+    match self.facing {
+      Facing::Left => pos.x < WorldUnit::LEFT + self.width / 2.,
+      Facing::Right => pos.x > WorldUnit::RIGHT - self.width / 2.,
+    }
+  }
+
+  fn walk_sprites<'a>(&self, character: &'a Character) -> &'a [Handle<Image>] {
+    match self.facing {
+      Facing::Left => character
+        .walk_left_sprites
+        .as_deref()
+        .unwrap_or(&character.walk_sprites),
+      Facing::Right => &character.walk_sprites,
+    }
+  }
+
+  fn current_asset(&self, character: &Character) -> Handle<Image> {
     if self.state.is_wet() {
-      match self.character {
-        Character::Boy => npc_assets.wet_boy_sprite.clone_weak(),
-        Character::Nun => npc_assets.wet_nun_sprite.clone_weak(),
-        Character::OldMan => npc_assets.wet_old_man_sprite.clone_weak(),
-        Character::SchoolGirl => npc_assets.wet_school_girl_sprite.clone_weak(),
-        Character::Baker => npc_assets.wet_baker_sprite.clone_weak(),
-        Character::BearGirl => npc_assets.wet_bear_girl_sprite.clone_weak(),
-        Character::BoyCap => npc_assets.wet_boy_cap_sprite.clone_weak(),
-      }
+      character.wet_sprite.clone_weak()
     } else {
-      match self.character {
-        Character::Boy => npc_assets.boy_sprites[self.animation_idx].clone_weak(),
-        Character::Nun => npc_assets.nun_sprite.clone_weak(),
-        Character::OldMan => npc_assets.old_man_sprites[self.animation_idx].clone_weak(),
-        Character::SchoolGirl => npc_assets.school_girl_sprites[self.animation_idx].clone_weak(),
-        Character::Baker => npc_assets.baker_sprites[self.animation_idx].clone_weak(),
-        Character::BearGirl => npc_assets.bear_girl_sprites[self.animation_idx].clone_weak(),
-        Character::BoyCap => npc_assets.boy_cap_sprites[self.animation_idx].clone_weak(),
-      }
+      let walk_sprites = self.walk_sprites(character);
+      walk_sprites[self.animation_idx % walk_sprites.len()].clone_weak()
     }
   }
 
-  fn tick(&mut self, delta: Duration, npc_assets: &NpcAssets, sprite: &mut Sprite) {
+  /// Characters without left-facing frames are drawn mirrored when walking
+  /// left.
+  fn is_mirrored(&self, character: &Character) -> bool {
+    !self.state.is_wet() && self.facing == Facing::Left && character.walk_left_sprites.is_none()
+  }
+
+  fn tick(&mut self, delta: Duration, character: &Character, sprite: &mut Sprite) {
     self.timer.tick(delta);
     if self.timer.just_finished() {
-      self.animation_idx = (self.animation_idx + 1) % self.character.num_states(npc_assets);
+      self.animation_idx = (self.animation_idx + 1) % self.walk_sprites(character).len();
     }
 
     self.state.tick(delta);
+    if let Some(stun) = &mut self.stun {
+      if stun.tick(delta).finished() {
+        self.stun = None;
+      }
+    }
 
-    sprite.image = self.current_asset(npc_assets);
+    sprite.image = self.current_asset(character);
+    sprite.flip_x = self.is_mirrored(character);
   }
 }
 
@@ -174,50 +217,59 @@ struct NpcBundle {
   sprite: Sprite,
   npc: Npc,
   pos: Position,
+  collider: Collider,
+  scope: StateScoped<InGame>,
 }
 
 impl NpcBundle {
-  // const BOY_WIDTH: f32 = 750.;
-  // const BOY_HEIGHT: f32 = 1250.;
-  const BOY_WIDTH: u32 = 589;
-  const BOY_HEIGHT: u32 = 656;
-  const ASPECT_RATIO: f32 = Self::BOY_HEIGHT as f32 / Self::BOY_WIDTH as f32;
-
-  const WIDTH: WorldUnit = WorldUnit::new(3.9);
-  const HEIGHT: WorldUnit = WorldUnit::new(3.9 * Self::ASPECT_RATIO);
-
   const Z_IDX: f32 = 1.;
 
-  fn bounding_rect() -> WorldRect {
-    WorldRect::new(Self::WIDTH, Self::HEIGHT)
-  }
+  fn new(roster: &Roster, character_idx: usize, facing: Facing, speed_scale: f32) -> Self {
+    let character = roster.character(character_idx);
+    let npc = Npc::new(character_idx, character, facing, speed_scale);
+    let pos = match facing {
+      Facing::Left => {
+        WorldVec2::new_normalized(1., NpcPlugin::GROUND_Y)
+          + WorldVec2 {
+            x: character.width / 2.,
+            y: character.height / 2.,
+          }
+      }
+      Facing::Right => {
+        WorldVec2::new_normalized(-1., NpcPlugin::GROUND_Y)
+          + WorldVec2 {
+            x: -character.width / 2.,
+            y: character.height / 2.,
+          }
+      }
+    };
 
-  fn spawn(mut commands: Commands, character: Character, pos: WorldVec2, npc_assets: &NpcAssets) {
-    let npc = Npc::new(character);
-    commands.spawn(NpcBundle {
-      sprite: Sprite::from_image(npc.current_asset(npc_assets)),
+    Self {
+      sprite: Sprite {
+        flip_x: npc.is_mirrored(character),
+        ..Sprite::from_image(npc.current_asset(character))
+      },
+      collider: Collider::sensor(Shape::Rect(npc.bounding_rect())),
       npc,
-      pos: Position::new(pos, Self::WIDTH, Self::BOY_WIDTH, Self::Z_IDX),
-    });
+      pos: Position::new(pos, character.width, character.image_width, Self::Z_IDX),
+      scope: StateScoped(InGame),
+    }
+  }
+
+  fn spawn(
+    mut commands: Commands,
+    roster: &Roster,
+    character_idx: usize,
+    facing: Facing,
+    speed_scale: f32,
+  ) {
+    commands.spawn(Self::new(roster, character_idx, facing, speed_scale));
   }
 }
 
 #[derive(Resource)]
 struct NpcAssets {
-  boy_sprites: [Handle<Image>; 4],
-  wet_boy_sprite: Handle<Image>,
-  nun_sprite: Handle<Image>,
-  wet_nun_sprite: Handle<Image>,
-  old_man_sprites: [Handle<Image>; 2],
-  wet_old_man_sprite: Handle<Image>,
-  school_girl_sprites: [Handle<Image>; 2],
-  wet_school_girl_sprite: Handle<Image>,
-  baker_sprites: [Handle<Image>; 2],
-  wet_baker_sprite: Handle<Image>,
-  bear_girl_sprites: [Handle<Image>; 2],
-  wet_bear_girl_sprite: Handle<Image>,
-  boy_cap_sprites: [Handle<Image>; 2],
-  wet_boy_cap_sprite: Handle<Image>,
+  roster: Handle<Roster>,
 }
 
 #[derive(Resource)]
@@ -226,11 +278,12 @@ struct NpcPluginState {
 }
 
 impl NpcPluginState {
-  const NPC_SPAWN_TIMER: Duration = Duration::from_secs(5);
-
   fn new() -> Self {
     Self {
-      spawn_timer: Timer::new(Self::NPC_SPAWN_TIMER, TimerMode::Repeating),
+      spawn_timer: Timer::new(
+        WaveSettings::for_wave(1).npc_spawn_period,
+        TimerMode::Repeating,
+      ),
     }
   }
 }
@@ -238,110 +291,155 @@ impl NpcPluginState {
 pub struct NpcPlugin;
 
 impl NpcPlugin {
-  fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let boy_sprites = [1, 2, 3, 2].map(|idx| asset_server.load(format!("boy/boy_{idx}_right.png")));
-    let wet_boy_sprite = asset_server.load("boy/boy_wet.png");
-
-    let nun_sprite = asset_server.load("nun/nun_right.png");
-    let wet_nun_sprite = asset_server.load("nun/nun_wet.png");
-
-    let old_man_sprites =
-      [1, 2].map(|idx| asset_server.load(format!("old_man/old_man_{idx}_right.png")));
-    let wet_old_man_sprite = asset_server.load("old_man/old_man_wet.png");
+  /// The normalized height of the ground that NPCs walk along.
+  pub const GROUND_Y: f32 = -0.78;
 
-    let school_girl_sprites =
-      [1, 2].map(|idx| asset_server.load(format!("school_girl/school_girl_{idx}_right.png")));
-    let wet_school_girl_sprite = asset_server.load("school_girl/school_girl_wet.png");
+  /// How much of their speed NPCs keep while wading through a puddle.
+  const WADING_SPEED: f32 = 0.5;
 
-    let baker_sprites = [1, 2].map(|idx| asset_server.load(format!("baker/baker_{idx}.png")));
-    let wet_baker_sprite = asset_server.load("baker/wet_baker.png");
-
-    let bear_girl_sprites =
-      [1, 2].map(|idx| asset_server.load(format!("bear_girl/bear_girl_{idx}.png")));
-    let wet_bear_girl_sprite = asset_server.load("bear_girl/bear_girl_mad.png");
-
-    let boy_cap_sprites = [1, 2].map(|idx| asset_server.load(format!("boy_cap/boy_cap_{idx}.png")));
-    let wet_boy_cap_sprite = asset_server.load("boy_cap/boy_cap_mad.png");
+  /// How long NPCs stand frozen after lightning strikes next to them.
+  const STUN_DURATION: Duration = Duration::from_secs(2);
 
+  fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(NpcAssets {
-      boy_sprites,
-      wet_boy_sprite,
-      nun_sprite,
-      wet_nun_sprite,
-      old_man_sprites,
-      wet_old_man_sprite,
-      school_girl_sprites,
-      wet_school_girl_sprite,
-      baker_sprites,
-      wet_baker_sprite,
-      bear_girl_sprites,
-      wet_bear_girl_sprite,
-      boy_cap_sprites,
-      wet_boy_cap_sprite,
+      roster: asset_server.load("characters.roster.ron"),
     });
-    commands.insert_resource(NpcPluginState::new());
+  }
+
+  fn reset_spawn_timer(mut state: ResMut<NpcPluginState>) {
+    *state = NpcPluginState::new();
   }
 
   fn spawn_npcs(
     commands: Commands,
     time: Res<Time>,
     mut state: ResMut<NpcPluginState>,
+    mut rng: ResMut<GameRng>,
     npc_assets: Res<NpcAssets>,
+    rosters: Res<Assets<Roster>>,
+    wave: Res<Wave>,
   ) {
+    let settings = wave.settings();
+    state.spawn_timer.set_duration(settings.npc_spawn_period);
     state.spawn_timer.tick(time.delta());
 
     if state.spawn_timer.just_finished() {
-      NpcBundle::spawn(
-        commands,
-        Character::random_character(),
-        WorldVec2::new_normalized(-1., -0.78)
-          + WorldVec2 {
-            x: -NpcBundle::WIDTH / 2.,
-            y: NpcBundle::HEIGHT / 2.,
-          },
-        &npc_assets,
-      );
+      if let Some(roster) = rosters.get(&npc_assets.roster) {
+        NpcBundle::spawn(
+          commands,
+          roster,
+          roster.random_character(&mut rng),
+          Facing::random(&mut rng),
+          settings.random_npc_speed_scale(&mut rng),
+        );
+      }
     }
   }
 
-  fn control_npcs(
+  /// Soaks NPCs that rain lands on. NPCs are safe until they have fully
+  /// entered the screen.
+  pub fn soak_npcs(
     mut commands: Commands,
-    mut npc_query: Query<(&mut Npc, &Position, &mut MoveComponent)>,
-    rain_query: Query<(Entity, &Position), With<Rain>>,
+    mut collisions: EventReader<RainCollision>,
+    mut lose_life: EventWriter<LoseLife>,
+    mut splashes: EventWriter<Splash>,
+    mut npc_query: Query<(&mut Npc, &Position)>,
+    rain_query: Query<(&Position, &MoveComponent), (With<Rain>, With<InPlay>)>,
   ) {
-    for (mut npc, npc_pos, mut npc_vel) in &mut npc_query {
-      let npc_pos = npc_pos.pos;
-
-      if npc_pos.x > WorldUnit::LEFT + NpcBundle::WIDTH / 2. {
-        for (rain_entity, rain_pos) in &rain_query {
-          let dist = rain_pos.pos - npc_pos;
-          let closest_point = NpcBundle::bounding_rect().closest_point(dist);
-          if (closest_point - dist).length_squared() < RainBundle::RADIUS.squared() {
-            npc.state.absorb_rain();
-            commands.entity(rain_entity).despawn();
-          }
+    for collision in collisions.read() {
+      let Ok((mut npc, npc_pos)) = npc_query.get_mut(collision.collider) else {
+        continue;
+      };
+      let Ok((rain_pos, rain_vel)) = rain_query.get(collision.rain) else {
+        continue;
+      };
+      if npc.has_entered(npc_pos.pos) {
+        if npc.state.absorb_rain() {
+          lose_life.send(LoseLife);
+        }
+        splashes.send(Splash {
+          pos: rain_pos.pos,
+          normal: WorldVec2::Y,
+          speed: WorldUnit::new(rain_vel.delta.to_untyped().length()),
+        });
+        RainPool::recycle(&mut commands, collision.rain);
+      }
+    }
+  }
+
+  fn stun_npcs(
+    mut strikes: EventReader<LightningStrike>,
+    mut npc_query: Query<(&mut Npc, &Position)>,
+  ) {
+    for strike in strikes.read() {
+      for (mut npc, npc_pos) in &mut npc_query {
+        if strike.covers(npc_pos.pos.x) {
+          npc.stun = Some(Timer::new(Self::STUN_DURATION, TimerMode::Once));
         }
       }
+    }
+  }
 
-      if !npc.state.is_wet() {
-        npc_vel.delta = Npc::WALK_SPEED * Vec2::X;
+  fn control_npcs(mut npc_query: Query<(&Npc, &mut MoveComponent)>) {
+    for (npc, mut npc_vel) in &mut npc_query {
+      if !npc.state.is_wet() && npc.stun.is_none() {
+        npc_vel.delta = npc.walk_speed * npc.facing.direction();
       } else {
         npc_vel.delta = WorldVec2::ZERO;
       }
     }
   }
 
+  /// Slows NPCs walking through puddles, and soaks them if the puddle is
+  /// deep enough.
+  fn wade_through_puddles(
+    mut lose_life: EventWriter<LoseLife>,
+    puddles: Query<(&Puddle, &Position)>,
+    mut npc_query: Query<(&mut Npc, &Position, &mut MoveComponent)>,
+  ) {
+    for (mut npc, npc_pos, mut npc_vel) in &mut npc_query {
+      let Some((puddle, _)) = puddles
+        .iter()
+        .find(|(puddle, puddle_pos)| puddle.covers(puddle_pos, npc_pos.pos.x))
+      else {
+        continue;
+      };
+      npc_vel.delta *= Self::WADING_SPEED;
+      if puddle.is_deep() && npc.has_entered(npc_pos.pos) && npc.state.absorb_rain() {
+        lose_life.send(LoseLife);
+      }
+    }
+  }
+
+  /// Remembers which umbrella is sheltering each NPC. When several are, the
+  /// lowest one takes the credit.
+  fn track_shelter(
+    umbrellas: Query<(&PlayerId, &Position, &Player)>,
+    mut npc_query: Query<(&mut Npc, &Position)>,
+  ) {
+    for (mut npc, npc_pos) in &mut npc_query {
+      let shelter = umbrellas
+        .iter()
+        .filter(|(_, player_pos, player)| {
+          let diff = player_pos.pos - npc_pos.pos;
+          diff.y > WorldUnit::ZERO && diff.x.abs() < player.width() / 2.
+        })
+        .min_by(|(_, a, _), (_, b, _)| a.pos.y.to_untyped().total_cmp(&b.pos.y.to_untyped()));
+      if let Some((&id, ..)) = shelter {
+        npc.sheltered_by = Some(id);
+      }
+    }
+  }
+
   fn score_npcs(
     mut commands: Commands,
     mut earn_point: EventWriter<EarnPoint>,
-    query: Query<(Entity, &Position), With<Npc>>,
+    query: Query<(Entity, &Position, &Npc)>,
   ) {
-    for (entity, pos) in &query {
-      let pos = pos.pos;
-
-      if pos.x > WorldUnit::RIGHT - NpcBundle::WIDTH / 2. {
+    for (entity, pos, npc) in &query {
+      if npc.has_arrived(pos.pos) {
         commands.entity(entity).despawn();
-        earn_point.send(EarnPoint);
+        earn_point.send(EarnPoint { player: npc.sheltered_by });
       }
     }
   }
@@ -350,10 +448,16 @@ impl NpcPlugin {
     mut commands: Commands,
     time: Res<Time>,
     npc_assets: Res<NpcAssets>,
+    rosters: Res<Assets<Roster>>,
     mut query: Query<(Entity, &mut Sprite, &mut Npc)>,
   ) {
+    let Some(roster) = rosters.get(&npc_assets.roster) else {
+      return;
+    };
+
     for (entity, mut sprite, mut npc) in &mut query {
-      npc.tick(time.delta(), &npc_assets, &mut sprite);
+      let character = roster.character(npc.character);
+      npc.tick(time.delta(), character, &mut sprite);
 
       if npc.state.should_despawn() {
         commands.entity(entity).despawn();
@@ -362,17 +466,174 @@ impl NpcPlugin {
   }
 }
 
+#[cfg(test)]
+impl NpcPlugin {
+  pub fn roster_loaded(world: &World) -> bool {
+    let roster = &world.resource::<NpcAssets>().roster;
+    world.resource::<Assets<Roster>>().contains(roster)
+  }
+
+  /// Spawns an NPC of the first character at `pos`, walking right. The roster
+  /// must already be loaded.
+  pub fn spawn_npc_at(world: &mut World, pos: WorldVec2) -> Entity {
+    let roster = world
+      .resource::<Assets<Roster>>()
+      .get(&world.resource::<NpcAssets>().roster)
+      .unwrap();
+    let mut bundle = NpcBundle::new(roster, 0, Facing::Right, 1.);
+    bundle.pos.pos = pos;
+    world.spawn(bundle).id()
+  }
+}
+
 impl Plugin for NpcPlugin {
   fn build(&self, app: &mut App) {
     app
+      .init_asset::<Roster>()
+      .init_asset_loader::<RosterLoader>()
+      .insert_resource(NpcPluginState::new())
       .add_systems(
         Startup,
         Self::initialize_plugin.after(WorldInitPlugin::world_init),
       )
+      .add_systems(
+        OnEnter(InGame),
+        Self::reset_spawn_timer.after(RngPlugin::reseed_rng),
+      )
       .add_systems(
         FixedUpdate,
-        (Self::control_npcs, Self::spawn_npcs, Self::score_npcs),
+        (
+          Self::soak_npcs.after(CollisionPlugin::handle_rain_collisions),
+          (
+            Self::stun_npcs,
+            Self::control_npcs,
+            Self::wade_through_puddles,
+          )
+            .chain(),
+          Self::spawn_npcs.in_set(DrawRandom::Npcs),
+          (Self::track_shelter, Self::score_npcs).chain(),
+        )
+          .run_if(GameStatePlugin::playing),
       )
-      .add_systems(Update, Self::npc_tick);
+      .add_systems(FixedUpdate, Self::npc_tick.run_if(GameStatePlugin::playing));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::asset::Assets;
+  use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+  use googletest::prelude::*;
+
+  use crate::{
+    lightning::LightningStrike,
+    lives::LoseLife,
+    movable::MoveComponent,
+    puddle::RainLanded,
+    rain::{InPlay, RainKind},
+    roster::Roster,
+    score::EarnPoint,
+    test_harness::TestApp,
+  };
+
+  use super::{Facing, Npc, NpcAssets, NpcBundle, NpcPlugin};
+
+  fn loaded_app() -> TestApp {
+    let mut app = TestApp::new();
+    app.wait_until(NpcPlugin::roster_loaded).start_playing();
+    app
+  }
+
+  fn npc_bundle(app: &TestApp, facing: Facing) -> NpcBundle {
+    let world = app.world();
+    let roster = world
+      .resource::<Assets<Roster>>()
+      .get(&world.resource::<NpcAssets>().roster)
+      .unwrap();
+    NpcBundle::new(roster, 0, facing, 1.)
+  }
+
+  #[gtest]
+  fn npc_gets_wet_when_hit_by_rain() {
+    let mut app = loaded_app();
+    app.track_events::<LoseLife>();
+
+    let mut bundle = npc_bundle(&app, Facing::Right);
+    bundle.pos.pos = WorldVec2::new_normalized(-0.5, -0.78);
+    let npc_pos = bundle.pos.pos;
+    let npc = app.world_mut().spawn(bundle).id();
+    let rain = app.spawn_drop(npc_pos, RainKind::Drop, WorldVec2::ZERO);
+
+    app.step();
+
+    let world = app.world();
+    expect_that!(world.get::<Npc>(npc).unwrap().state.is_wet(), eq(true));
+    expect_that!(world.entity(rain).contains::<InPlay>(), eq(false));
+    expect_that!(app.event_count::<LoseLife>(), eq(1));
+  }
+
+  #[gtest]
+  fn npc_gets_wet_wading_through_a_deep_puddle() {
+    let mut app = loaded_app();
+    app.track_events::<LoseLife>();
+
+    let mut bundle = npc_bundle(&app, Facing::Right);
+    bundle.pos.pos.x = WorldUnit::ZERO;
+    let npc = app.world_mut().spawn(bundle).id();
+    for _ in 0..50 {
+      app
+        .world_mut()
+        .send_event(RainLanded { x: WorldUnit::ZERO });
+    }
+    app.run_for(Duration::from_millis(50));
+
+    expect_that!(
+      app.world().get::<Npc>(npc).unwrap().state.is_wet(),
+      eq(true)
+    );
+    expect_that!(app.event_count::<LoseLife>(), eq(1));
+  }
+
+  #[gtest]
+  fn lightning_stuns_npcs_where_it_strikes() {
+    let mut app = loaded_app();
+    let mut bundle = npc_bundle(&app, Facing::Right);
+    bundle.pos.pos.x = WorldUnit::ZERO;
+    let npc = app.world_mut().spawn(bundle).id();
+    app.world_mut().send_event(LightningStrike {
+      x: WorldUnit::new(1.),
+      half_width: WorldUnit::new(3.),
+    });
+    app.step();
+
+    let npc_vel = app.world().get::<MoveComponent>(npc).unwrap();
+    expect_that!(npc_vel.delta, eq(WorldVec2::ZERO));
+
+    app.run_for(NpcPlugin::STUN_DURATION);
+    let npc_vel = app.world().get::<MoveComponent>(npc).unwrap();
+    expect_that!(npc_vel.delta.x.to_untyped(), gt(0.));
+  }
+
+  #[gtest]
+  fn npc_reaching_goal_earns_point() {
+    for facing in [Facing::Left, Facing::Right] {
+      let mut app = loaded_app();
+      app.track_events::<EarnPoint>();
+
+      let mut bundle = npc_bundle(&app, facing);
+      let edge = WorldUnit::RIGHT - bundle.npc.width / 2. - WorldUnit::new(0.01);
+      bundle.pos.pos.x = match facing {
+        Facing::Left => -edge,
+        Facing::Right => edge,
+      };
+      let npc = app.world_mut().spawn(bundle).id();
+
+      app.run_for(Duration::from_millis(100));
+
+      expect_that!(app.event_count::<EarnPoint>(), eq(1));
+      expect_that!(app.world().get_entity(npc).is_ok(), eq(false));
+    }
   }
 }
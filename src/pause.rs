@@ -0,0 +1,216 @@
+use bevy::{
+  app::{App, Plugin, Update},
+  asset::AssetServer,
+  color::Color,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    event::EventReader,
+    query::Changed,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut},
+  },
+  hierarchy::{BuildChildren, ChildBuild},
+  input::{gamepad::Gamepad, keyboard::KeyCode, ButtonInput},
+  state::{
+    condition::in_state,
+    state::{NextState, OnEnter, OnExit, State},
+    state_scoped::StateScoped,
+  },
+  text::{JustifyText, TextFont, TextLayout},
+  time::{Time, Virtual},
+  ui::{
+    widget::{Button, Text},
+    AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Node, PositionType,
+    UiRect, Val,
+  },
+  utils::default,
+  window::WindowFocused,
+};
+
+use crate::{
+  controls::{Action, Controls},
+  game_state::GameState,
+  settings::SettingsState,
+};
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+  Resume,
+  Restart,
+  Controls,
+  Quit,
+}
+
+impl PauseButton {
+  const ALL: [Self; 4] = [Self::Resume, Self::Restart, Self::Controls, Self::Quit];
+
+  fn label(&self) -> &'static str {
+    match self {
+      Self::Resume => "Resume",
+      Self::Restart => "Restart",
+      Self::Controls => "Controls",
+      Self::Quit => "Quit",
+    }
+  }
+}
+
+#[derive(Bundle)]
+struct PauseMenuBundle {
+  node: Node,
+  background: BackgroundColor,
+  scope: StateScoped<GameState>,
+}
+
+#[derive(Bundle)]
+struct PauseButtonBundle {
+  button: Button,
+  node: Node,
+  background: BackgroundColor,
+  pause_button: PauseButton,
+}
+
+#[derive(Bundle)]
+struct PauseTextBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+}
+
+pub struct PausePlugin;
+
+impl PausePlugin {
+  const OVERLAY_COLOR: Color = Color::srgba(0., 0., 0., 0.6);
+  const BUTTON_COLOR: Color = Color::srgba(0.2, 0.2, 0.3, 0.9);
+  const HOVERED_BUTTON_COLOR: Color = Color::srgba(0.35, 0.35, 0.5, 0.9);
+
+  fn show_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Rubik-VariableFont_wght.ttf");
+
+    commands
+      .spawn(PauseMenuBundle {
+        node: Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          height: Val::Percent(100.),
+          flex_direction: FlexDirection::Column,
+          align_items: AlignItems::Center,
+          justify_content: JustifyContent::Center,
+          row_gap: Val::Px(20.),
+          ..default()
+        },
+        background: BackgroundColor(Self::OVERLAY_COLOR),
+        scope: StateScoped(GameState::Paused),
+      })
+      .with_children(|parent| {
+        parent.spawn(PauseTextBundle {
+          text: Text::new("Paused"),
+          font: TextFont {
+            font: font.clone(),
+            font_size: 67.0,
+            ..default()
+          },
+          layout: TextLayout::new_with_justify(JustifyText::Center),
+        });
+
+        for pause_button in PauseButton::ALL {
+          parent
+            .spawn(PauseButtonBundle {
+              button: Button,
+              node: Node {
+                width: Val::Px(300.),
+                padding: UiRect::all(Val::Px(10.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+              },
+              background: BackgroundColor(Self::BUTTON_COLOR),
+              pause_button,
+            })
+            .with_child(PauseTextBundle {
+              text: Text::new(pause_button.label()),
+              font: TextFont {
+                font: font.clone(),
+                font_size: 40.0,
+                ..default()
+              },
+              layout: TextLayout::new_with_justify(JustifyText::Center),
+            });
+        }
+      });
+  }
+
+  fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+  }
+
+  fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+  }
+
+  fn toggle_pause(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    controls: Res<Controls>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+  ) {
+    if !controls.just_pressed(Action::Pause, &keyboard_input, &gamepads) {
+      return;
+    }
+
+    match state.get() {
+      GameState::Playing => next_state.set(GameState::Paused),
+      GameState::Paused => next_state.set(GameState::Playing),
+      _ => {}
+    }
+  }
+
+  fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<GameState>>,
+  ) {
+    if focus_events.read().any(|event| !event.focused) {
+      next_state.set(GameState::Paused);
+    }
+  }
+
+  fn handle_buttons(
+    mut query: Query<(&Interaction, &PauseButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings: ResMut<NextState<SettingsState>>,
+  ) {
+    for (interaction, pause_button, mut background) in &mut query {
+      match interaction {
+        Interaction::Pressed => match pause_button {
+          PauseButton::Resume => next_state.set(GameState::Playing),
+          PauseButton::Restart => next_state.set(GameState::Restarting),
+          PauseButton::Controls => next_settings.set(SettingsState::Open),
+          PauseButton::Quit => next_state.set(GameState::Title),
+        },
+        Interaction::Hovered => background.0 = Self::HOVERED_BUTTON_COLOR,
+        Interaction::None => background.0 = Self::BUTTON_COLOR,
+      }
+    }
+  }
+}
+
+impl Plugin for PausePlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(
+        OnEnter(GameState::Paused),
+        (Self::show_pause_menu, Self::pause_time),
+      )
+      .add_systems(OnExit(GameState::Paused), Self::unpause_time)
+      .add_systems(
+        Update,
+        (
+          Self::toggle_pause.run_if(in_state(SettingsState::Closed)),
+          Self::pause_on_focus_loss.run_if(in_state(GameState::Playing)),
+          Self::handle_buttons
+            .run_if(in_state(GameState::Paused))
+            .run_if(in_state(SettingsState::Closed)),
+        ),
+      );
+  }
+}
@@ -1,17 +1,27 @@
+use std::{f32::consts::FRAC_PI_3, time::Duration};
+
 use bevy::{
-  app::{App, FixedUpdate, Plugin, Startup},
+  app::{App, FixedUpdate, Plugin, Update},
   asset::AssetServer,
+  color::Color,
   ecs::{
     bundle::Bundle,
+    change_detection::DetectChangesMut,
     component::Component,
-    query::{With, Without},
-    schedule::IntoSystemConfigs,
-    system::{Commands, Query, Res, Single},
-    world::World,
+    entity::Entity,
+    event::EventReader,
+    query::{Changed, With},
+    schedule::{
+      common_conditions::resource_changed, IntoSystemConfigs, IntoSystemSetConfigs, SystemSet,
+    },
+    system::{Commands, Query, Res},
   },
   image::Image,
-  input::{keyboard::KeyCode, ButtonInput},
+  input::{gamepad::Gamepad, keyboard::KeyCode, ButtonInput},
+  math::{Quat, Vec2},
   sprite::Sprite,
+  state::state::OnEnter,
+  time::{Time, Timer, TimerMode},
 };
 use bevy_world_space::{
   position::Position,
@@ -19,20 +29,101 @@ use bevy_world_space::{
 };
 
 use crate::{
+  collider::{Collider, CollisionPlugin, RainCollision, Shape},
+  controls::{Action, Controls},
+  game_state::{GameStatePlugin, InGame},
+  lobby::{InputDevice, LocalPlayers},
   movable::{MoveComponent, MovePlugin},
-  rain::{Rain, RainBundle},
+  wind::Wind,
 };
 
-/// Component that identifies the player.
-#[derive(Component)]
-#[require(MoveComponent)]
-struct Player;
+/// What a player is asking their umbrella to do this tick. Gameplay only
+/// reads input through this component, so it can be recorded and replayed.
+#[derive(Component, Clone, Copy, Default, PartialEq)]
+pub struct PlayerInput {
+  /// Desired velocity as a fraction of the umbrella's top speed, with each
+  /// component in `-1..=1`.
+  pub movement: Vec2,
+  /// Desired tilt of the canopy, from -1 for fully left to 1 for fully right.
+  pub tilt: f32,
+}
+
+/// Systems that fill in every `PlayerInput` from the input devices each tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadPlayerInput;
+
+/// A temporary change to the width of the umbrella.
+struct SizeModifier {
+  scale: f32,
+  timer: Timer,
+}
+
+/// Which of the local players an umbrella belongs to, counting from zero.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlayerId(pub usize);
+
+impl PlayerId {
+  /// Tints that tell the umbrellas apart. The first player's umbrella is left
+  /// as drawn.
+  const TINTS: [Color; LocalPlayers::MAX] = [
+    Color::WHITE,
+    Color::srgb(0.6, 0.75, 1.),
+    Color::srgb(0.6, 1., 0.6),
+    Color::srgb(1., 0.9, 0.5),
+  ];
+
+  pub fn label(&self) -> String {
+    format!("P{}", self.0 + 1)
+  }
+
+  fn tint(&self) -> Color {
+    Self::TINTS[self.0 % Self::TINTS.len()]
+  }
+}
+
+/// Component that identifies a player's umbrella.
+#[derive(Component, Default)]
+#[require(MoveComponent, PlayerInput)]
+pub struct Player {
+  size_modifier: Option<SizeModifier>,
+  /// Rotation of the canopy in radians, counterclockwise from upright.
+  tilt: f32,
+}
+
+impl Player {
+  pub fn width(&self) -> WorldUnit {
+    let scale = self
+      .size_modifier
+      .as_ref()
+      .map_or(1., |modifier| modifier.scale);
+    PlayerBundle::WIDTH * scale
+  }
+
+  /// The direction the canopy faces. Only things on this side of the umbrella
+  /// touch the canopy.
+  pub fn canopy_normal(&self) -> WorldVec2 {
+    let (sin, cos) = self.tilt.sin_cos();
+    WorldVec2::new(WorldUnit::new(-sin), WorldUnit::new(cos))
+  }
+
+  /// Scales the umbrella's width for `duration`, replacing any modifier
+  /// already in effect.
+  pub fn resize(&mut self, scale: f32, duration: Duration) {
+    self.size_modifier = Some(SizeModifier {
+      scale,
+      timer: Timer::new(duration, TimerMode::Once),
+    });
+  }
+}
 
 #[derive(Bundle)]
 struct PlayerBundle {
   sprite: Sprite,
   pos: Position,
   player: Player,
+  collider: Collider,
+  id: PlayerId,
+  device: InputDevice,
 }
 
 impl PlayerBundle {
@@ -44,105 +135,432 @@ impl PlayerBundle {
 
   const Z_IDX: f32 = 1.;
 
-  fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let texture = asset_server.load::<Image>("umbrella/umbrella.png");
+  /// Rain only bounces off the top of the canopy.
+  fn collider(width: WorldUnit) -> Collider {
+    Collider::solid(
+      Shape::HalfCircle(width / 2.),
+      PlayerPlugin::RAIN_RESTITUTION,
+      0.,
+    )
+  }
 
-    let sprite = Sprite::from_image(texture);
+  /// Where each umbrella starts a run, spread evenly across the screen.
+  fn start_position(id: PlayerId, players: usize) -> WorldVec2 {
+    let offset = (2 * id.0 + 1) as f32 / players as f32 - 1.;
+    WorldVec2::new(WorldUnit::RIGHT * (offset / 2.), WorldUnit::ZERO)
+  }
 
-    commands.queue(|world: &mut World| {
-      world.spawn(Self {
-        sprite,
-        pos: Position::new(WorldVec2::ZERO, Self::WIDTH, Self::IMG_WIDTH, Self::Z_IDX),
-        player: Player,
+  /// Gives every local player an umbrella, replacing the old ones whenever
+  /// players join or leave.
+  fn spawn_players(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    players: Res<LocalPlayers>,
+    umbrellas: Query<Entity, With<Player>>,
+  ) {
+    for entity in &umbrellas {
+      commands.entity(entity).despawn();
+    }
+
+    let texture = asset_server.load::<Image>("umbrella/umbrella.png");
+    for (idx, &device) in players.devices().iter().enumerate() {
+      let id = PlayerId(idx);
+      commands.spawn(Self {
+        sprite: Sprite {
+          color: id.tint(),
+          ..Sprite::from_image(texture.clone())
+        },
+        pos: Position::new(
+          Self::start_position(id, players.count()),
+          Self::WIDTH,
+          Self::IMG_WIDTH,
+          Self::Z_IDX,
+        ),
+        player: Player::default(),
+        collider: Self::collider(Self::WIDTH),
+        id,
+        device,
       });
-    });
+    }
   }
 }
 
 pub struct PlayerPlugin;
 
 impl PlayerPlugin {
-  const SPEED: WorldUnit = WorldUnit::new(16.);
+  pub const SPEED: WorldUnit = WorldUnit::new(16.);
+  /// The furthest the canopy can tilt from upright, in radians.
+  const MAX_TILT: f32 = FRAC_PI_3;
+  /// How quickly the canopy tilts, in radians per second.
+  const TILT_SPEED: f32 = 4.;
   const RAIN_RESTITUTION: f32 = 0.15;
+  /// Fraction of the wind speed that the umbrella is blown along at.
+  const WIND_FACTOR: f32 = 0.3;
+  /// How far the umbrella is pushed by each unit of momentum it takes from the
+  /// rain.
+  const KNOCKBACK: f32 = 0.03;
+
+  /// Stick deflections smaller than this are ignored, so a stick that does not
+  /// quite center itself doesn't drift the umbrella.
+  const STICK_DEAD_ZONE: f32 = 0.2;
+
+  /// Combines a pair of opposing actions into a value in `-1..=1`.
+  fn axis(pressed: impl Fn(Action) -> bool, positive: Action, negative: Action) -> f32 {
+    match (pressed(positive), pressed(negative)) {
+      (true, false) => 1.,
+      (false, true) => -1.,
+      _ => 0.,
+    }
+  }
+
+  fn movement(pressed: impl Fn(Action) -> bool) -> Vec2 {
+    Vec2::new(
+      Self::axis(&pressed, Action::MoveRight, Action::MoveLeft),
+      Self::axis(&pressed, Action::MoveUp, Action::MoveDown),
+    )
+  }
 
-  fn move_player(
+  /// Reads the keyboard for every player on it. This runs first, so it also
+  /// clears the input of players on gamepads.
+  pub fn read_keyboard(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<&mut MoveComponent, With<Player>>,
+    controls: Res<Controls>,
+    mut query: Query<(&InputDevice, &mut PlayerInput)>,
+  ) {
+    for (device, mut input) in &mut query {
+      let pressed = |action| match *device {
+        InputDevice::Keyboard => controls.key_pressed(action, &keyboard_input),
+        InputDevice::SplitKeyboard(half) => controls.key_pressed_on(action, half, &keyboard_input),
+        InputDevice::Gamepad(_) => false,
+      };
+      input.movement = Self::movement(pressed);
+      input.tilt = Self::axis(pressed, Action::TiltRight, Action::TiltLeft);
+    }
+  }
+
+  /// Scales a stick deflection so movement starts from zero at the edge of the
+  /// dead zone and reaches full speed at full deflection.
+  fn stick_movement(stick: Vec2) -> Vec2 {
+    let magnitude = stick.length();
+    if magnitude < Self::STICK_DEAD_ZONE {
+      return Vec2::ZERO;
+    }
+    let scaled = ((magnitude - Self::STICK_DEAD_ZONE) / (1. - Self::STICK_DEAD_ZONE)).min(1.);
+    stick * (scaled / magnitude)
+  }
+
+  /// Reads the sticks of every connected gamepad, with the left stick moving
+  /// and the right stick tilting. Each falls back to the buttons bound to it,
+  /// which default to the d-pad and triggers. Gamepads that haven't joined as
+  /// a player of their own steer the first player's umbrella. Whichever of
+  /// the gamepads and keyboard is pushed furthest wins, so they can be used
+  /// interchangeably.
+  pub fn read_gamepads(
+    gamepads: Query<(Entity, &Gamepad)>,
+    controls: Res<Controls>,
+    mut query: Query<(&PlayerId, &InputDevice, &mut PlayerInput)>,
   ) {
-    for mut move_component in &mut query {
-      match (
-        keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp),
-        keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown),
-      ) {
-        (true, false) => move_component.delta.y = Self::SPEED,
-        (false, true) => move_component.delta.y = -Self::SPEED,
-        _ => move_component.delta.y = WorldUnit::ZERO,
+    for (entity, gamepad) in &gamepads {
+      let joined = query
+        .iter()
+        .any(|(_, &device, _)| device == InputDevice::Gamepad(entity));
+      let Some((_, _, mut input)) = query.iter_mut().find(|&(&id, &device, _)| {
+        if joined {
+          device == InputDevice::Gamepad(entity)
+        } else {
+          id == PlayerId(0)
+        }
+      }) else {
+        continue;
+      };
+
+      let pressed = |action| controls.button_pressed(action, gamepad);
+
+      let mut movement = Self::stick_movement(gamepad.left_stick());
+      if movement == Vec2::ZERO {
+        movement = Self::movement(pressed);
       }
-      match (
-        keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight),
-        keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft),
-      ) {
-        (true, false) => move_component.delta.x = Self::SPEED,
-        (false, true) => move_component.delta.x = -Self::SPEED,
-        _ => move_component.delta.x = WorldUnit::ZERO,
+      if movement.length_squared() > input.movement.length_squared() {
+        input.movement = movement;
       }
+
+      let mut tilt = Self::stick_movement(gamepad.right_stick()).x;
+      if tilt == 0. {
+        tilt = Self::axis(pressed, Action::TiltRight, Action::TiltLeft);
+      }
+      if tilt.abs() > input.tilt.abs() {
+        input.tilt = tilt;
+      }
+    }
+  }
+
+  pub fn move_player(
+    wind: Res<Wind>,
+    mut query: Query<(&PlayerInput, &mut MoveComponent), With<Player>>,
+  ) {
+    for (input, mut move_component) in &mut query {
+      move_component.delta = WorldVec2::new(
+        Self::SPEED * input.movement.x,
+        Self::SPEED * input.movement.y,
+      );
+      move_component.delta.x += wind.speed() * Self::WIND_FACTOR;
     }
   }
 
-  fn snap_in_bounds(mut query: Query<&mut Position, With<Player>>) {
-    for mut pos in &mut query {
+  /// Rotates the canopy towards the tilt the player is asking for.
+  fn tilt_umbrella(time: Res<Time>, mut query: Query<(&PlayerInput, &mut Player, &mut Position)>) {
+    let max_step = Self::TILT_SPEED * time.delta_secs();
+    for (input, mut player, mut pos) in &mut query {
+      let target = -input.tilt * Self::MAX_TILT;
+      if player.tilt != target {
+        player.tilt += (target - player.tilt).clamp(-max_step, max_step);
+      }
+      pos.rotation = Quat::from_rotation_z(player.tilt);
+    }
+  }
+
+  /// Puts the umbrellas back where they start, so every run begins the same
+  /// way.
+  fn reset_player(
+    players: Res<LocalPlayers>,
+    mut query: Query<(&PlayerId, &mut Player, &mut Position, &mut MoveComponent)>,
+  ) {
+    for (&id, mut player, mut pos, mut move_component) in &mut query {
+      *player = Player::default();
+      pos.pos = PlayerBundle::start_position(id, players.count());
+      pos.rotation = Quat::IDENTITY;
+      move_component.delta = WorldVec2::ZERO;
+    }
+  }
+
+  /// Pushes umbrellas back when rain bounces off them. Only hail is heavy
+  /// enough to make much of a difference.
+  fn knock_back_umbrellas(
+    mut collisions: EventReader<RainCollision>,
+    mut umbrellas: Query<&mut Position, With<Player>>,
+  ) {
+    for collision in collisions.read() {
+      if let Ok(mut pos) = umbrellas.get_mut(collision.collider) {
+        pos.pos += collision.impulse * Self::KNOCKBACK;
+      }
+    }
+  }
+
+  fn snap_in_bounds(mut query: Query<(&mut Position, &Player)>) {
+    for (mut pos, player) in &mut query {
+      let half_width = player.width() / 2.;
       let pos = &mut pos.pos;
       pos.x = pos
         .x
-        .min(WorldUnit::RIGHT - PlayerBundle::WIDTH / 2.)
-        .max(WorldUnit::LEFT + PlayerBundle::WIDTH / 2.);
+        .min(WorldUnit::RIGHT - half_width)
+        .max(WorldUnit::LEFT + half_width);
       pos.y = pos
         .y
-        .min(WorldUnit::TOP - PlayerBundle::WIDTH / 2.)
-        .max(WorldUnit::BOTTOM + PlayerBundle::WIDTH / 2.);
+        .min(WorldUnit::TOP - half_width)
+        .max(WorldUnit::BOTTOM + half_width);
     }
   }
 
-  fn handle_rain_collisions(
-    player: Single<(&Position, &MoveComponent), With<Player>>,
-    mut rain_query: Query<(&Position, &mut MoveComponent), (With<Rain>, Without<Player>)>,
-  ) {
-    let (player_pos, player_vel) = player.into_inner();
-    for (rain_pos, mut rain_vel) in &mut rain_query {
-      let diff = rain_pos.pos - player_pos.pos;
-      let dist2 = diff.length_squared();
-      if diff.y >= WorldUnit::ZERO
-        && dist2 < (PlayerBundle::WIDTH / 2. + RainBundle::RADIUS).squared()
-      {
-        let relative_vel = rain_vel.delta - player_vel.delta;
-
-        let diff = diff.normalized();
-        let dot = diff.dot(relative_vel);
-        if dot < 0. {
-          let orthogonal_vel = diff * dot;
-          let impulse = -(1. + Self::RAIN_RESTITUTION) * orthogonal_vel;
-          rain_vel.delta += impulse;
-        }
+  fn tick_size_modifiers(time: Res<Time>, mut query: Query<&mut Player>) {
+    for mut player in &mut query {
+      let expired = player
+        .bypass_change_detection()
+        .size_modifier
+        .as_mut()
+        .is_some_and(|modifier| modifier.timer.tick(time.delta()).finished());
+      if expired {
+        player.size_modifier = None;
       }
     }
   }
+
+  fn resize_umbrella(mut query: Query<(&Player, &mut Position, &mut Collider), Changed<Player>>) {
+    for (player, mut pos, mut collider) in &mut query {
+      *collider = PlayerBundle::collider(player.width());
+      let rotation = pos.rotation;
+      *pos = Position::new(
+        pos.pos,
+        player.width(),
+        PlayerBundle::IMG_WIDTH,
+        PlayerBundle::Z_IDX,
+      );
+      pos.rotation = rotation;
+    }
+  }
 }
 
 impl Plugin for PlayerPlugin {
   fn build(&self, app: &mut App) {
     app
-      .add_systems(Startup, PlayerBundle::spawn_player)
+      .add_systems(
+        Update,
+        PlayerBundle::spawn_players.run_if(resource_changed::<LocalPlayers>),
+      )
+      .add_systems(OnEnter(InGame), Self::reset_player)
+      .configure_sets(FixedUpdate, ReadPlayerInput.before(Self::move_player))
+      .add_systems(
+        FixedUpdate,
+        (Self::read_keyboard, Self::read_gamepads)
+          .chain()
+          .in_set(ReadPlayerInput),
+      )
       .add_systems(
         FixedUpdate,
         Self::move_player.before(MovePlugin::apply_moves),
       )
       .add_systems(
         FixedUpdate,
-        Self::snap_in_bounds.after(MovePlugin::apply_moves),
+        (
+          Self::knock_back_umbrellas
+            .after(CollisionPlugin::handle_rain_collisions)
+            .before(MovePlugin::apply_moves)
+            .run_if(GameStatePlugin::playing),
+          Self::snap_in_bounds.after(MovePlugin::apply_moves),
+        )
+          .chain(),
       )
       .add_systems(
         FixedUpdate,
-        Self::handle_rain_collisions.before(MovePlugin::apply_moves),
+        (
+          Self::tilt_umbrella,
+          Self::tick_size_modifiers,
+          Self::resize_umbrella,
+        )
+          .chain()
+          .after(Self::move_player)
+          .run_if(GameStatePlugin::playing),
       );
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::{
+    ecs::{entity::Entity, query::With},
+    input::{keyboard::KeyCode, ButtonInput},
+    math::Vec2,
+  };
+  use bevy_world_space::{
+    position::Position,
+    world_unit::{WorldUnit, WorldVec2},
+  };
+  use googletest::prelude::*;
+
+  use crate::{rain::RainKind, test_harness::TestApp};
+
+  use super::{Player, PlayerPlugin};
+
+  #[gtest]
+  fn rain_bounces_off_umbrella() {
+    let mut app = TestApp::new();
+    app.start_playing();
+
+    let world = app.world_mut();
+    let (player_pos, player) = world.query::<(&Position, &Player)>().single(world);
+    let top_of_umbrella = player_pos.pos
+      + WorldVec2 {
+        x: WorldUnit::ZERO,
+        y: player.width() / 2.,
+      };
+    let falling = WorldVec2::new(WorldUnit::ZERO, WorldUnit::new(-10.));
+    let rain = app.spawn_drop(top_of_umbrella, RainKind::Drop, falling);
+
+    app.step();
+
+    expect_that!(app.velocity(rain).y.to_untyped(), gt(0.));
+  }
+
+  #[gtest]
+  fn tilted_umbrella_deflects_rain_sideways() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    app
+      .world_mut()
+      .resource_mut::<ButtonInput<KeyCode>>()
+      .press(KeyCode::KeyE);
+    app.run_for(Duration::from_secs(1));
+
+    let world = app.world_mut();
+    let (player_pos, player) = world.query::<(&Position, &Player)>().single(world);
+    expect_that!(player.canopy_normal().x.to_untyped(), gt(0.));
+
+    let on_canopy = player_pos.pos + player.canopy_normal() * (player.width() / 2.).to_untyped();
+    let falling = WorldVec2::new(WorldUnit::ZERO, WorldUnit::new(-10.));
+    let rain = app.spawn_drop(on_canopy, RainKind::Drop, falling);
+
+    app.step();
+
+    expect_that!(app.velocity(rain).x.to_untyped(), gt(0.));
+  }
+
+  /// Drops a `kind` of rain onto the canopy, in `direction` from the middle
+  /// of the umbrella, returning the drop and where the umbrella started.
+  fn drop_on_umbrella(app: &mut TestApp, direction: Vec2, kind: RainKind) -> (Entity, WorldVec2) {
+    let world = app.world_mut();
+    let (player_pos, player) = world.query::<(&Position, &Player)>().single(world);
+    let start = player_pos.pos;
+    let on_canopy = start + (player.width() / 2.) * direction.normalize();
+    let falling = WorldVec2::new(WorldUnit::ZERO, WorldUnit::new(-30.));
+    (app.spawn_drop(on_canopy, kind, falling), start)
+  }
+
+  #[gtest]
+  fn hail_knocks_the_umbrella_back() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    let (_, start) = drop_on_umbrella(&mut app, Vec2::Y, RainKind::Hail);
+
+    app.step();
+
+    let world = app.world_mut();
+    let player_pos = world
+      .query_filtered::<&Position, With<Player>>()
+      .single(world);
+    expect_that!((start.y - player_pos.pos.y).to_untyped(), gt(0.2));
+  }
+
+  #[gtest]
+  fn heavy_drops_punch_through_the_rim() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    let rim = Vec2::new(1., 0.2);
+    let (heavy, _) = drop_on_umbrella(&mut app, rim, RainKind::Heavy);
+    let (drop, _) = drop_on_umbrella(&mut app, rim, RainKind::Drop);
+
+    app.step();
+
+    expect_that!(app.velocity(heavy).x.to_untyped(), lt(1.));
+    expect_that!(app.velocity(drop).x.to_untyped(), gt(3.));
+  }
+
+  #[gtest]
+  fn snow_never_bounces() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    let (snow, _) = drop_on_umbrella(&mut app, Vec2::Y, RainKind::Snow);
+
+    app.step();
+
+    expect_that!(app.velocity(snow).y.to_untyped(), le(0.));
+  }
+
+  #[gtest]
+  fn stick_inside_dead_zone_is_ignored() {
+    expect_that!(
+      PlayerPlugin::stick_movement(Vec2::new(0.1, -0.1)),
+      eq(Vec2::ZERO)
+    );
+  }
+
+  #[gtest]
+  fn stick_movement_scales_with_deflection() {
+    let half = PlayerPlugin::stick_movement(Vec2::new(0.6, 0.));
+    expect_that!(half.x, approx_eq(0.5));
+    expect_that!(half.y, eq(0.));
+
+    let full = PlayerPlugin::stick_movement(Vec2::new(0., -1.));
+    expect_that!(full.y, approx_eq(-1.));
+  }
+}
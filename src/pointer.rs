@@ -0,0 +1,212 @@
+use bevy::{
+  app::{App, FixedUpdate, Plugin, Update},
+  ecs::{
+    query::With,
+    schedule::IntoSystemConfigs,
+    system::{Query, Res, ResMut, Resource},
+  },
+  input::{gamepad::Gamepad, keyboard::KeyCode, mouse::MouseButton, touch::Touches, ButtonInput},
+  math::Vec2,
+  render::camera::{Camera, OrthographicProjection},
+  transform::components::GlobalTransform,
+  window::{PrimaryWindow, Window},
+};
+use bevy_world_space::{position::Position, world_unit::WorldVec2};
+
+use crate::{
+  controls::{Action, Controls},
+  player::{PlayerId, PlayerInput, PlayerPlugin, ReadPlayerInput},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PointerMode {
+  Follow,
+  /// The umbrella moves along with the pointer, keeping whatever offset it
+  /// had when the pointer was pressed, so a finger doesn't cover it.
+  Drag,
+}
+
+struct DragAnchor {
+  pointer: WorldVec2,
+  umbrella: WorldVec2,
+}
+
+#[derive(Resource)]
+struct PointerControl {
+  mode: PointerMode,
+  drag_anchor: Option<DragAnchor>,
+}
+
+/// Moves the first player's umbrella with the mouse while the left button is
+/// held, or with a finger on touch screens.
+pub struct PointerPlugin;
+
+impl PointerPlugin {
+  /// The umbrella still never moves faster than `PlayerPlugin::SPEED`.
+  const FOLLOW_RATE: f32 = 8.;
+
+  /// Converts a point in the camera's render space to world space. The camera
+  /// shows the whole screen, so the edges of its view are the edges of the
+  /// world.
+  fn view_to_world(point: Vec2, projection: &OrthographicProjection) -> WorldVec2 {
+    let normalized = (point - projection.area.center()) / projection.area.half_size();
+    WorldVec2::new_normalized(normalized.x, normalized.y)
+  }
+
+  fn movement_towards(from: WorldVec2, to: WorldVec2) -> Vec2 {
+    let offset = to - from;
+    let offset = Vec2::new(offset.x.to_untyped(), offset.y.to_untyped());
+    (offset * Self::FOLLOW_RATE / PlayerPlugin::SPEED.to_untyped()).clamp_length_max(1.)
+  }
+
+  fn pressed_pointer(
+    window: &Window,
+    mouse_input: &ButtonInput<MouseButton>,
+    touches: &Touches,
+  ) -> Option<Vec2> {
+    touches
+      .iter()
+      .next()
+      .map(|touch| touch.position())
+      .or_else(|| {
+        mouse_input
+          .pressed(MouseButton::Left)
+          .then(|| window.cursor_position())
+          .flatten()
+      })
+  }
+
+  /// Toggles between follow and drag mode with `Action::Ability` or a two
+  /// finger tap.
+  fn toggle_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    controls: Res<Controls>,
+    touches: Res<Touches>,
+    mut control: ResMut<PointerControl>,
+  ) {
+    let two_finger_tap =
+      touches.iter().count() == 2 && touches.iter_just_pressed().next().is_some();
+    if controls.just_pressed(Action::Ability, &keyboard_input, &gamepads) || two_finger_tap {
+      control.mode = match control.mode {
+        PointerMode::Follow => PointerMode::Drag,
+        PointerMode::Drag => PointerMode::Follow,
+      };
+      control.drag_anchor = None;
+    }
+  }
+
+  fn read_pointer(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut players: Query<(&PlayerId, &Position, &mut PlayerInput)>,
+    mut control: ResMut<PointerControl>,
+  ) {
+    let Ok(window) = windows.get_single() else {
+      return;
+    };
+    let Ok((camera, camera_transform, projection)) = cameras.get_single() else {
+      return;
+    };
+    let Some((_, player, mut input)) = players.iter_mut().find(|(&id, ..)| id == PlayerId(0))
+    else {
+      return;
+    };
+    let Some(pointer) = Self::pressed_pointer(window, &mouse_input, &touches) else {
+      control.drag_anchor = None;
+      return;
+    };
+
+    let Ok(pointer) = camera.viewport_to_world_2d(camera_transform, pointer) else {
+      return;
+    };
+    let pointer = Self::view_to_world(pointer, projection);
+    let umbrella = player.pos;
+    let target = match control.mode {
+      PointerMode::Follow => pointer,
+      PointerMode::Drag => {
+        let anchor = control
+          .drag_anchor
+          .get_or_insert(DragAnchor { pointer, umbrella });
+        anchor.umbrella + (pointer - anchor.pointer)
+      }
+    };
+
+    let movement = Self::movement_towards(umbrella, target);
+    if movement.length_squared() > input.movement.length_squared() {
+      input.movement = movement;
+    }
+  }
+}
+
+impl Plugin for PointerPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .insert_resource(PointerControl {
+        mode: PointerMode::Follow,
+        drag_anchor: None,
+      })
+      .add_systems(Update, Self::toggle_mode)
+      .add_systems(
+        FixedUpdate,
+        Self::read_pointer
+          .after(PlayerPlugin::read_gamepads)
+          .in_set(ReadPlayerInput),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::{
+    math::{Rect, Vec2},
+    render::camera::OrthographicProjection,
+  };
+  use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+  use googletest::prelude::*;
+
+  use super::PointerPlugin;
+
+  #[gtest]
+  fn view_corners_map_to_screen_corners() {
+    let projection = OrthographicProjection {
+      area: Rect::new(-400., -225., 400., 225.),
+      ..OrthographicProjection::default_2d()
+    };
+
+    let top_left = PointerPlugin::view_to_world(Vec2::new(-400., 225.), &projection);
+    expect_that!(
+      top_left.x.to_untyped(),
+      approx_eq(WorldUnit::LEFT.to_untyped())
+    );
+    expect_that!(
+      top_left.y.to_untyped(),
+      approx_eq(WorldUnit::TOP.to_untyped())
+    );
+
+    let bottom_right = PointerPlugin::view_to_world(Vec2::new(400., -225.), &projection);
+    expect_that!(
+      bottom_right.x.to_untyped(),
+      approx_eq(WorldUnit::RIGHT.to_untyped())
+    );
+    expect_that!(
+      bottom_right.y.to_untyped(),
+      approx_eq(WorldUnit::BOTTOM.to_untyped())
+    );
+  }
+
+  #[gtest]
+  fn following_is_capped_at_full_speed() {
+    let far = PointerPlugin::movement_towards(WorldVec2::ZERO, WorldVec2::new_normalized(1., 0.));
+    expect_that!(far.length(), approx_eq(1.));
+
+    let near = PointerPlugin::movement_towards(
+      WorldVec2::ZERO,
+      WorldVec2::new(WorldUnit::new(0.5), WorldUnit::ZERO),
+    );
+    expect_that!(near.x, lt(1.));
+    expect_that!(near.x, gt(0.));
+  }
+}
@@ -0,0 +1,259 @@
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  color::Color,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::{Event, EventReader},
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res},
+  },
+  math::Vec2,
+  sprite::Sprite,
+  state::state_scoped::StateScoped,
+  time::Time,
+};
+use bevy_world_space::{
+  position::Position,
+  world_unit::{WorldUnit, WorldVec2},
+};
+
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  npc::NpcPlugin,
+  rain::RainPlugin,
+};
+
+/// Sent when a raindrop falls all the way to the ground, wherever it lands.
+#[derive(Event)]
+pub struct RainLanded {
+  pub x: WorldUnit,
+}
+
+/// Water collecting on the ground, which spreads out as more rain lands in
+/// it and slowly evaporates.
+#[derive(Component)]
+pub struct Puddle {
+  /// How many drops' worth of water the puddle holds.
+  volume: f32,
+}
+
+impl Puddle {
+  const MIN_WIDTH: WorldUnit = WorldUnit::new(1.);
+  const WIDTH_PER_DROP: WorldUnit = WorldUnit::new(0.1);
+  const MAX_VOLUME: f32 = 60.;
+  /// Puddles holding this much soak the feet of anyone walking through.
+  const DEEP_VOLUME: f32 = 40.;
+
+  pub fn width(&self) -> WorldUnit {
+    Self::MIN_WIDTH + Self::WIDTH_PER_DROP * self.volume
+  }
+
+  pub fn is_deep(&self) -> bool {
+    self.volume >= Self::DEEP_VOLUME
+  }
+
+  /// True if the puddle at `pos` reaches `x`.
+  pub fn covers(&self, pos: &Position, x: WorldUnit) -> bool {
+    (x - pos.pos.x).abs() < self.width() / 2.
+  }
+
+  fn fill(&mut self, volume: f32) {
+    self.volume = (self.volume + volume).min(Self::MAX_VOLUME);
+  }
+}
+
+#[derive(Bundle)]
+struct PuddleBundle {
+  sprite: Sprite,
+  pos: Position,
+  puddle: Puddle,
+  scope: StateScoped<InGame>,
+}
+
+impl PuddleBundle {
+  const IMG_WIDTH: u32 = 100;
+  const IMG_HEIGHT: u32 = 6;
+
+  const COLOR: Color = Color::srgba(0.35, 0.45, 0.6, 0.7);
+
+  const Z_IDX: f32 = 0.8;
+
+  fn new(x: WorldUnit, volume: f32) -> Self {
+    let puddle = Puddle { volume };
+    Self {
+      sprite: Sprite::from_color(
+        Self::COLOR,
+        Vec2::new(Self::IMG_WIDTH as f32, Self::IMG_HEIGHT as f32),
+      ),
+      pos: Position::new(
+        WorldVec2::new(x, WorldVec2::new_normalized(0., NpcPlugin::GROUND_Y).y),
+        puddle.width(),
+        Self::IMG_WIDTH,
+        Self::Z_IDX,
+      ),
+      puddle,
+      scope: StateScoped(InGame),
+    }
+  }
+}
+
+pub struct PuddlePlugin;
+
+impl PuddlePlugin {
+  const VOLUME_PER_DROP: f32 = 1.;
+
+  /// How many drops' worth of water every puddle loses each second.
+  const EVAPORATION_RATE: f32 = 2.;
+
+  fn collect_rain(
+    mut commands: Commands,
+    mut landings: EventReader<RainLanded>,
+    mut puddles: Query<(&mut Puddle, &Position)>,
+  ) {
+    // Puddles started this tick, which aren't spawned yet.
+    let mut new_puddles: Vec<PuddleBundle> = Vec::new();
+    for landing in landings.read() {
+      // Drops blown off the side of the screen land where no one sees them.
+      if landing.x.abs() > WorldUnit::RIGHT {
+        continue;
+      }
+      if let Some((mut puddle, _)) = puddles
+        .iter_mut()
+        .find(|(puddle, pos)| puddle.covers(pos, landing.x))
+      {
+        puddle.fill(Self::VOLUME_PER_DROP);
+      } else if let Some(new_puddle) = new_puddles
+        .iter_mut()
+        .find(|new_puddle| new_puddle.puddle.covers(&new_puddle.pos, landing.x))
+      {
+        new_puddle.puddle.fill(Self::VOLUME_PER_DROP);
+      } else {
+        new_puddles.push(PuddleBundle::new(landing.x, Self::VOLUME_PER_DROP));
+      }
+    }
+    commands.spawn_batch(new_puddles);
+  }
+
+  /// Combines puddles that have spread into each other into a single puddle,
+  /// centered on where most of their water is.
+  fn merge_puddles(mut commands: Commands, mut query: Query<(Entity, &mut Puddle, &mut Position)>) {
+    let mut puddles: Vec<_> = query.iter_mut().collect();
+    puddles.sort_by(|(_, _, a), (_, _, b)| a.pos.x.to_untyped().total_cmp(&b.pos.x.to_untyped()));
+
+    let mut puddles = puddles.into_iter();
+    let Some(mut merged) = puddles.next() else {
+      return;
+    };
+    for (entity, puddle, pos) in puddles {
+      let (_, merged_puddle, merged_pos) = &mut merged;
+      let gap = pos.pos.x - merged_pos.pos.x;
+      if gap >= (merged_puddle.width() + puddle.width()) / 2. {
+        merged = (entity, puddle, pos);
+        continue;
+      }
+      let share = puddle.volume / (merged_puddle.volume + puddle.volume);
+      merged_pos.pos.x += gap * share;
+      merged_puddle.fill(puddle.volume);
+      merged_pos.width = merged_puddle.width();
+      commands.entity(entity).despawn();
+    }
+  }
+
+  fn evaporate_puddles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Puddle, &mut Position)>,
+  ) {
+    for (entity, mut puddle, mut pos) in &mut query {
+      puddle.volume -= Self::EVAPORATION_RATE * time.delta_secs();
+      if puddle.volume <= 0. {
+        commands.entity(entity).despawn();
+      } else {
+        pos.width = puddle.width();
+      }
+    }
+  }
+}
+
+impl Plugin for PuddlePlugin {
+  fn build(&self, app: &mut App) {
+    app.add_event::<RainLanded>().add_systems(
+      FixedUpdate,
+      (
+        Self::collect_rain.after(RainPlugin::recycle_raindrops),
+        Self::merge_puddles,
+        Self::evaporate_puddles,
+      )
+        .chain()
+        .run_if(GameStatePlugin::playing),
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::ecs::world::World;
+  use bevy_world_space::world_unit::WorldUnit;
+  use googletest::prelude::*;
+
+  use crate::test_harness::TestApp;
+
+  use super::{Puddle, RainLanded};
+
+  fn puddle_volumes(world: &mut World) -> Vec<f32> {
+    world
+      .query::<&Puddle>()
+      .iter(world)
+      .map(|puddle| puddle.volume)
+      .collect()
+  }
+
+  #[gtest]
+  fn nearby_drops_share_a_puddle_that_dries_up() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    for x in [0., 0.3, 10., 40.] {
+      app
+        .world_mut()
+        .send_event(RainLanded { x: WorldUnit::new(x) });
+    }
+    app.step();
+
+    let volumes = puddle_volumes(app.world_mut());
+    expect_that!(
+      volumes,
+      unordered_elements_are![near(2., 0.1), near(1., 0.1)]
+    );
+
+    app.run_for(Duration::from_secs(1));
+    expect_that!(puddle_volumes(app.world_mut()), is_empty());
+  }
+
+  #[gtest]
+  fn puddles_that_grow_into_each_other_merge() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    for x in [0., 1.2] {
+      app
+        .world_mut()
+        .send_event(RainLanded { x: WorldUnit::new(x) });
+    }
+    app.step();
+    expect_that!(puddle_volumes(app.world_mut()).len(), eq(2));
+
+    for _ in 0..10 {
+      app
+        .world_mut()
+        .send_event(RainLanded { x: WorldUnit::ZERO });
+    }
+    app.step();
+    expect_that!(
+      puddle_volumes(app.world_mut()),
+      elements_are![near(12., 0.1)]
+    );
+  }
+}
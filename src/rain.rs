@@ -1,37 +1,140 @@
-use std::time::Duration;
-
 use bevy::{
   app::{App, FixedUpdate, Plugin, Startup},
   asset::{AssetServer, Handle},
+  color::Color,
   ecs::{
     bundle::Bundle,
     component::Component,
     entity::Entity,
+    event::EventWriter,
     query::With,
+    schedule::IntoSystemConfigs,
     system::{Commands, Query, Res, ResMut, Resource},
     world::World,
   },
   image::Image,
   math::{ops::atan2, Quat},
+  render::view::Visibility,
   sprite::Sprite,
-  time::{Time, Timer, TimerMode},
+  state::{state::OnEnter, state_scoped::StateScoped},
+  time::Time,
 };
 use bevy_world_space::{
   position::Position,
   world_unit::{WorldUnit, WorldVec2},
 };
 
-use crate::{gravity::GravityComponent, movable::MoveComponent};
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  gravity::GravityComponent,
+  movable::MoveComponent,
+  npc::NpcPlugin,
+  puddle::RainLanded,
+  rain_pattern::RainEmitter,
+  rng::{DrawRandom, GameRng, RngPlugin},
+  splash::Splash,
+  wave::{Wave, WaveSettings},
+  wind::Wind,
+};
+
+/// The kinds of precipitation, which each fall, bounce and hit differently.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RainKind {
+  #[default]
+  Drop,
+  /// Big drops that punch through the rim of an umbrella.
+  Heavy,
+  /// Heavy enough to knock umbrellas back.
+  Hail,
+  /// Drifts with the wind and never bounces.
+  Snow,
+}
+
+impl RainKind {
+  pub const MAX_RADIUS: WorldUnit = WorldUnit::new(0.6);
+
+  pub fn radius(&self) -> WorldUnit {
+    match self {
+      Self::Drop => WorldUnit::new(0.4),
+      Self::Heavy => Self::MAX_RADIUS,
+      Self::Hail => WorldUnit::new(0.5),
+      Self::Snow => WorldUnit::new(0.35),
+    }
+  }
+
+  /// How much of a push the drop gives whatever it bounces off.
+  pub fn mass(&self) -> f32 {
+    match self {
+      Self::Drop => 0.01,
+      Self::Heavy => 0.04,
+      Self::Hail => 0.4,
+      Self::Snow => 0.005,
+    }
+  }
+
+  fn gravity_scale(&self) -> f32 {
+    match self {
+      Self::Drop => 1.,
+      Self::Heavy => 1.3,
+      Self::Hail => 1.5,
+      Self::Snow => 0.2,
+    }
+  }
+
+  /// Scales the restitution of anything the drop bounces off.
+  pub fn bounciness(&self) -> f32 {
+    match self {
+      Self::Drop => 1.,
+      Self::Heavy => 0.3,
+      Self::Hail => 3.,
+      Self::Snow => 0.,
+    }
+  }
+
+  /// How quickly the wind carries the drop along, compared to a raindrop.
+  pub fn wind_drag(&self) -> f32 {
+    match self {
+      Self::Drop => 1.,
+      Self::Heavy => 0.6,
+      Self::Hail => 0.3,
+      Self::Snow => 4.,
+    }
+  }
+
+  pub fn pierces_rims(&self) -> bool {
+    *self == Self::Heavy
+  }
+
+  fn tint(&self) -> Color {
+    match self {
+      Self::Drop => Color::WHITE,
+      Self::Heavy => Color::srgb(0.5, 0.6, 1.),
+      Self::Hail => Color::srgb(0.8, 0.9, 0.9),
+      Self::Snow => Color::srgba(1., 1., 1., 0.8),
+    }
+  }
+}
 
 #[derive(Component)]
 #[require(MoveComponent, GravityComponent)]
-pub struct Rain;
+pub struct Rain {
+  pub kind: RainKind,
+}
+
+/// Missing from drops waiting in the `RainPool`. Stored in a sparse set so
+/// pooling a drop doesn't move its other components.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct InPlay;
 
 #[derive(Bundle)]
 pub struct RainBundle {
   sprite: Sprite,
   pos: Position,
   rain: Rain,
+  in_play: InPlay,
+  gravity: GravityComponent,
+  scope: StateScoped<InGame>,
 }
 
 impl RainBundle {
@@ -41,17 +144,82 @@ impl RainBundle {
   const RAIN_WIDTH: u32 = 233;
   // const RAIN_HEIGHT: f32 = 390.;
 
-  pub const RADIUS: WorldUnit = WorldUnit::new(0.4);
-
   const Z_IDX: f32 = 0.;
 
-  fn spawn_rain(mut commands: Commands, rain_image: Handle<Image>, pos: WorldVec2) {
+  pub fn new(rain_image: Handle<Image>, pos: WorldVec2, kind: RainKind) -> Self {
+    Self {
+      sprite: Sprite {
+        color: kind.tint(),
+        ..Sprite::from_image(rain_image)
+      },
+      pos: Position::new(pos, kind.radius(), Self::RAIN_WIDTH, Self::Z_IDX),
+      rain: Rain { kind },
+      in_play: InPlay,
+      gravity: GravityComponent { scale: kind.gravity_scale() },
+      scope: StateScoped(InGame),
+    }
+  }
+
+  fn spawn_rain(
+    commands: &mut Commands,
+    rain_image: Handle<Image>,
+    pos: WorldVec2,
+    kind: RainKind,
+  ) {
     commands.queue(move |world: &mut World| {
-      world.spawn(Self {
-        sprite: Sprite::from_image(rain_image),
-        pos: Position::new(pos, Self::RADIUS, Self::RAIN_WIDTH, Self::Z_IDX),
-        rain: Rain,
-      });
+      let idle = world.resource_mut::<RainPool>().idle.pop();
+      match idle.and_then(|entity| world.get_entity_mut(entity).ok()) {
+        Some(mut entity) => {
+          entity.insert((
+            Self::new(rain_image, pos, kind),
+            MoveComponent::default(),
+            Visibility::Inherited,
+          ));
+        }
+        None => {
+          world.spawn(Self::new(rain_image, pos, kind));
+        }
+      }
+    });
+  }
+}
+
+/// Hidden raindrops that new drops reuse instead of spawning entities.
+#[derive(Resource)]
+pub struct RainPool {
+  /// Drops that leave play while the pool is full are despawned.
+  pub max_idle: usize,
+  idle: Vec<Entity>,
+}
+
+impl Default for RainPool {
+  fn default() -> Self {
+    Self { max_idle: 4096, idle: Vec::new() }
+  }
+}
+
+impl RainPool {
+  pub fn recycle(commands: &mut Commands, entity: Entity) {
+    commands.queue(move |world: &mut World| {
+      // A drop can leave play in several ways on the same tick.
+      let Ok(mut drop) = world.get_entity_mut(entity) else {
+        return;
+      };
+      if drop.take::<InPlay>().is_none() {
+        return;
+      }
+      drop.insert((
+        MoveComponent::default(),
+        GravityComponent { scale: 0. },
+        Visibility::Hidden,
+      ));
+
+      let mut pool = world.resource_mut::<RainPool>();
+      if pool.idle.len() >= pool.max_idle {
+        world.despawn(entity);
+        return;
+      }
+      pool.idle.push(entity);
     });
   }
 }
@@ -59,44 +227,96 @@ impl RainBundle {
 #[derive(Resource)]
 struct RainResources {
   rain_image: Handle<Image>,
-  timer: Timer,
+  emitter: RainEmitter,
+  spawn_xs: Vec<f32>,
 }
 
 pub struct RainPlugin;
 
 impl RainPlugin {
-  // const TIMEOUT: Duration = Duration::from_secs(1);
-  const TIMEOUT: Duration = Duration::from_millis(200);
-
-  fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
+  fn initialize_plugin(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut rng: ResMut<GameRng>,
+  ) {
     let rain_image = asset_server.load::<Image>("raindrop/raindrop.png");
     commands.insert_resource(RainResources {
       rain_image,
-      timer: Timer::new(Self::TIMEOUT, TimerMode::Repeating),
+      emitter: RainEmitter::new(WaveSettings::for_wave(1).rain_pattern, &mut rng),
+      spawn_xs: Vec::new(),
     });
   }
 
-  fn spawn_raindrops(commands: Commands, time: Res<Time>, mut resources: ResMut<RainResources>) {
-    if resources.timer.tick(time.delta()).just_finished() {
+  fn reset_emitter(mut resources: ResMut<RainResources>, mut rng: ResMut<GameRng>) {
+    resources.emitter = RainEmitter::new(WaveSettings::for_wave(1).rain_pattern, &mut rng);
+  }
+
+  /// The pooled drops were despawned along with the rest of the last run.
+  fn empty_pool(mut pool: ResMut<RainPool>) {
+    pool.idle.clear();
+  }
+
+  fn spawn_raindrops(
+    mut commands: Commands,
+    time: Res<Time>,
+    wave: Res<Wave>,
+    wind: Res<Wind>,
+    mut rng: ResMut<GameRng>,
+    mut resources: ResMut<RainResources>,
+  ) {
+    let settings = wave.settings();
+    let RainResources { rain_image, emitter, spawn_xs } = &mut *resources;
+
+    emitter.set_pattern(settings.rain_pattern, &mut rng);
+    spawn_xs.clear();
+    emitter.tick(
+      &mut rng,
+      time.delta_secs(),
+      settings.rain_period.as_secs_f32().recip(),
+      settings.rain_intensity_variation,
+      |x| spawn_xs.push(x),
+    );
+    for &x in spawn_xs.iter() {
       RainBundle::spawn_rain(
-        commands,
-        resources.rain_image.clone_weak(),
-        WorldVec2::new_normalized(2. * fastrand::f32() - 1., 1.),
+        &mut commands,
+        rain_image.clone_weak(),
+        WorldVec2::new_normalized(x, 1.) + WorldVec2::new(wind.spawn_offset(), WorldUnit::ZERO),
+        settings.random_rain_kind(&mut rng),
       );
     }
   }
 
-  fn despawn_raindrops(mut commands: Commands, query: Query<(Entity, &Position), With<Rain>>) {
-    let min_y = WorldUnit::BOTTOM - RainBundle::RADIUS;
-    let x_bound = WorldUnit::RIGHT + RainBundle::RADIUS;
-    for (entity, Position { pos, .. }) in &query {
-      if pos.y < min_y || !(-x_bound..x_bound).contains(&pos.x) {
-        commands.entity(entity).despawn();
+  pub fn recycle_raindrops(
+    mut commands: Commands,
+    mut splashes: EventWriter<Splash>,
+    mut landings: EventWriter<RainLanded>,
+    query: Query<(Entity, &Position, &MoveComponent), (With<Rain>, With<InPlay>)>,
+  ) {
+    let ground_y = WorldVec2::new_normalized(0., NpcPlugin::GROUND_Y).y;
+    let x_bound = WorldUnit::RIGHT + RainKind::MAX_RADIUS;
+    for (entity, Position { pos, .. }, MoveComponent { delta }) in &query {
+      // Drops may spawn off to the side when the wind is blowing, so only
+      // recycle those that are moving away from the screen.
+      let leaving_left = pos.x < -x_bound && delta.x < WorldUnit::ZERO;
+      let leaving_right = pos.x > x_bound && delta.x > WorldUnit::ZERO;
+      let landed = pos.y < ground_y;
+      if landed {
+        splashes.send(Splash {
+          pos: WorldVec2::new(pos.x, ground_y),
+          normal: WorldVec2::Y,
+          speed: -delta.y,
+        });
+        landings.send(RainLanded { x: pos.x });
+      }
+      if landed || leaving_left || leaving_right {
+        RainPool::recycle(&mut commands, entity);
       }
     }
   }
 
-  fn rotate_raindrops(mut query: Query<(&MoveComponent, &mut Position), With<Rain>>) {
+  fn rotate_raindrops(
+    mut query: Query<(&MoveComponent, &mut Position), (With<Rain>, With<InPlay>)>,
+  ) {
     for (movement, mut pos) in &mut query {
       let delta = movement.delta.try_normalize().unwrap_or(-WorldVec2::Y);
       let angle = atan2(delta.x.to_untyped(), -delta.y.to_untyped());
@@ -108,14 +328,142 @@ impl RainPlugin {
 impl Plugin for RainPlugin {
   fn build(&self, app: &mut App) {
     app
+      .init_resource::<RainPool>()
       .add_systems(Startup, Self::initialize_plugin)
+      .add_systems(
+        OnEnter(InGame),
+        (
+          Self::reset_emitter.after(RngPlugin::reseed_rng),
+          Self::empty_pool,
+        ),
+      )
       .add_systems(
         FixedUpdate,
         (
-          Self::spawn_raindrops,
-          Self::despawn_raindrops,
+          Self::spawn_raindrops
+            .in_set(DrawRandom::Rain)
+            .run_if(GameStatePlugin::playing),
+          Self::recycle_raindrops,
           Self::rotate_raindrops,
         ),
       );
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use bevy::{
+    asset::Handle,
+    ecs::{entity::Entity, query::With},
+    render::view::Visibility,
+  };
+  use bevy_world_space::world_unit::WorldVec2;
+  use googletest::prelude::*;
+
+  use crate::{
+    npc::NpcPlugin,
+    puddle::{Puddle, RainLanded},
+    test_harness::TestApp,
+  };
+
+  use super::{InPlay, Rain, RainBundle, RainKind, RainPool};
+
+  /// Drops a raindrop just below the ground, where it leaves play on the next
+  /// tick.
+  fn spawn_fallen_drop_at(app: &mut TestApp, x: f32) -> Entity {
+    let below_ground = WorldVec2::new_normalized(x, NpcPlugin::GROUND_Y - 0.05);
+    app.spawn_drop(below_ground, RainKind::Drop, WorldVec2::ZERO)
+  }
+
+  fn spawn_fallen_drop(app: &mut TestApp) -> Entity {
+    spawn_fallen_drop_at(app, 0.)
+  }
+
+  fn spawn_pooled_drop(app: &mut TestApp) {
+    let world = app.world_mut();
+    RainBundle::spawn_rain(
+      &mut world.commands(),
+      Handle::default(),
+      WorldVec2::new_normalized(0., 0.5),
+      RainKind::Drop,
+    );
+    world.flush();
+  }
+
+  #[gtest]
+  fn fallen_drops_are_reused() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    let drop = spawn_fallen_drop(&mut app);
+    let table = app.world().entity(drop).location().table_id;
+    app.step();
+
+    let world = app.world();
+    expect_that!(world.entity(drop).location().table_id, eq(table));
+    expect_that!(world.entity(drop).contains::<InPlay>(), eq(false));
+    expect_that!(world.get::<Visibility>(drop), some(eq(&Visibility::Hidden)));
+
+    spawn_pooled_drop(&mut app);
+    let world = app.world();
+    expect_that!(world.entity(drop).location().table_id, eq(table));
+    expect_that!(world.entity(drop).contains::<InPlay>(), eq(true));
+    expect_that!(
+      world.get::<Visibility>(drop),
+      some(eq(&Visibility::Inherited))
+    );
+  }
+
+  #[gtest]
+  fn despawned_pooled_drops_are_replaced() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    let drop = spawn_fallen_drop(&mut app);
+    app.step();
+    app.world_mut().despawn(drop);
+
+    spawn_pooled_drop(&mut app);
+    let world = app.world_mut();
+    expect_that!(
+      world
+        .query_filtered::<(), (With<Rain>, With<InPlay>)>()
+        .iter(world)
+        .count(),
+      eq(1)
+    );
+  }
+
+  #[gtest]
+  fn drops_past_the_capacity_are_despawned() {
+    let mut app = TestApp::new();
+    app.start_playing();
+    app.world_mut().resource_mut::<RainPool>().max_idle = 1;
+    let pooled = spawn_fallen_drop(&mut app);
+    let despawned = spawn_fallen_drop(&mut app);
+    app.step();
+
+    let world = app.world_mut();
+    expect_that!(world.get_entity(pooled).is_ok(), eq(true));
+    expect_that!(world.get_entity(despawned).is_ok(), eq(false));
+    expect_that!(
+      world
+        .query_filtered::<(), (With<Rain>, With<InPlay>)>()
+        .iter(world)
+        .count(),
+      eq(0)
+    );
+  }
+
+  #[gtest]
+  fn drops_land_on_the_ground_line() {
+    let mut app = TestApp::new();
+    app.start_playing().track_events::<RainLanded>();
+    let landed = spawn_fallen_drop_at(&mut app, 0.5);
+    spawn_fallen_drop_at(&mut app, 1.2);
+    app.step();
+
+    let world = app.world_mut();
+    expect_that!(world.entity(landed).contains::<InPlay>(), eq(false));
+    expect_that!(world.query::<&Puddle>().iter(world).count(), eq(1));
+    expect_that!(app.event_count::<RainLanded>(), eq(2));
+  }
+}
@@ -0,0 +1,146 @@
+use std::f32::consts::TAU;
+
+/// How raindrops are distributed across the sky. Positions and widths are in
+/// normalized screen coordinates, where the screen spans -1 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RainPattern {
+  /// Drops fall uniformly across the screen.
+  Drizzle,
+  /// Drops fall in tight clusters at random places.
+  Bursts { drops_per_burst: u32, spread: f32 },
+  /// A dense band of rain that sweeps back and forth across the screen.
+  Curtain { width: f32, sweep_period: f32 },
+  /// Rain falls only beneath a few clouds, which drift across the sky.
+  Clouds {
+    count: usize,
+    width: f32,
+    drift_speed: f32,
+  },
+}
+
+struct Cloud {
+  x: f32,
+  velocity: f32,
+}
+
+/// Decides where and when raindrops spawn, following a `RainPattern` at an
+/// intensity that varies continuously over time.
+pub struct RainEmitter {
+  pattern: RainPattern,
+  elapsed: f32,
+  /// Drops that are owed but have not been spawned yet.
+  pending: f32,
+  clouds: Vec<Cloud>,
+}
+
+impl RainEmitter {
+  /// Periods of the two waves that make up the intensity curve. They are
+  /// chosen to rarely line up, so the curve does not obviously repeat.
+  const INTENSITY_PERIODS: [f32; 2] = [17., 7.3];
+
+  pub fn new(pattern: RainPattern, rng: &mut fastrand::Rng) -> Self {
+    let mut emitter = Self {
+      pattern,
+      elapsed: 0.,
+      pending: 0.,
+      clouds: Vec::new(),
+    };
+    emitter.reset_clouds(rng);
+    emitter
+  }
+
+  pub fn set_pattern(&mut self, pattern: RainPattern, rng: &mut fastrand::Rng) {
+    if self.pattern != pattern {
+      self.pattern = pattern;
+      self.reset_clouds(rng);
+    }
+  }
+
+  fn reset_clouds(&mut self, rng: &mut fastrand::Rng) {
+    self.clouds = match self.pattern {
+      RainPattern::Clouds { count, drift_speed, .. } => (0..count)
+        .map(|_| Cloud {
+          x: 2. * rng.f32() - 1.,
+          velocity: if rng.bool() {
+            drift_speed
+          } else {
+            -drift_speed
+          },
+        })
+        .collect(),
+      _ => Vec::new(),
+    };
+  }
+
+  /// A multiplier on the rain rate that varies smoothly between
+  /// `1 - variation` and `1 + variation`.
+  fn intensity(&self, variation: f32) -> f32 {
+    let [slow, fast] = Self::INTENSITY_PERIODS.map(|period| (TAU * self.elapsed / period).sin());
+    1. + variation * (0.6 * slow + 0.4 * fast)
+  }
+
+  fn random_near(rng: &mut fastrand::Rng, center: f32, width: f32) -> f32 {
+    center + width * (rng.f32() - 0.5)
+  }
+
+  /// Advances the emitter by `dt` seconds at an average of `rate` drops per
+  /// second, calling `spawn` with the normalized x coordinate of every drop
+  /// that should be spawned.
+  pub fn tick(
+    &mut self,
+    rng: &mut fastrand::Rng,
+    dt: f32,
+    rate: f32,
+    variation: f32,
+    mut spawn: impl FnMut(f32),
+  ) {
+    self.elapsed += dt;
+    self.pending += rate * self.intensity(variation) * dt;
+
+    for cloud in &mut self.clouds {
+      cloud.x += cloud.velocity * dt;
+      if !(-1. ..=1.).contains(&cloud.x) {
+        cloud.x = cloud.x.clamp(-1., 1.);
+        cloud.velocity = -cloud.velocity;
+      }
+    }
+
+    match self.pattern {
+      RainPattern::Drizzle => {
+        while self.pending >= 1. {
+          self.pending -= 1.;
+          spawn(2. * rng.f32() - 1.);
+        }
+      }
+      RainPattern::Bursts { drops_per_burst, spread } => {
+        let drops_per_burst = drops_per_burst.max(1);
+        while self.pending >= drops_per_burst as f32 {
+          self.pending -= drops_per_burst as f32;
+          let center = 2. * rng.f32() - 1.;
+          for _ in 0..drops_per_burst {
+            spawn(Self::random_near(rng, center, spread));
+          }
+        }
+      }
+      RainPattern::Curtain { width, sweep_period } => {
+        // Triangle wave from -1 to 1 and back over one sweep period.
+        let phase = (self.elapsed / sweep_period).fract();
+        let center = 1. - 4. * (phase - 0.5).abs();
+        while self.pending >= 1. {
+          self.pending -= 1.;
+          spawn(Self::random_near(rng, center, width));
+        }
+      }
+      RainPattern::Clouds { width, .. } => {
+        while self.pending >= 1. {
+          self.pending -= 1.;
+          if self.clouds.is_empty() {
+            continue;
+          }
+          let cloud = &self.clouds[rng.usize(..self.clouds.len())];
+          spawn(Self::random_near(rng, cloud.x, width));
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,558 @@
+use std::time::Duration;
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin, Startup, Update},
+  asset::AssetServer,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    query::With,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource, Single},
+  },
+  input::{keyboard::KeyCode, ButtonInput},
+  log::{error, info},
+  math::Vec2,
+  state::{
+    condition::in_state,
+    state::{NextState, OnEnter, State},
+  },
+  text::{JustifyText, TextFont, TextLayout},
+  time::{Fixed, Time, Virtual},
+  ui::{widget::Text, Node, PositionType, Val},
+  utils::default,
+};
+use bevy_world_space::world_init::WorldInitPlugin;
+use thiserror::Error;
+
+use crate::{
+  game_state::{GameState, GameStatePlugin, InGame},
+  lobby::LocalPlayers,
+  player::{PlayerId, PlayerInput, PlayerPlugin, ReadPlayerInput},
+  rng::GameRng,
+  score::ScoreResource,
+};
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+  #[error("Could not read replay: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Not a replay file")]
+  BadMagic,
+  #[error("Unsupported replay version {0}")]
+  UnsupportedVersion(u8),
+  #[error("Replay file is truncated")]
+  Truncated,
+  #[error("Replay has an invalid number of players: {0}")]
+  InvalidPlayerCount(usize),
+}
+
+/// The seed and per-tick input of a single run, which is everything needed
+/// to play it back.
+#[derive(Debug, PartialEq)]
+struct Recording {
+  seed: u64,
+  /// The score the run ended with, checked when the replay finishes.
+  score: u32,
+  players: usize,
+  /// Every player's movement and tilt for every fixed tick, quantized to one
+  /// byte per axis.
+  inputs: Vec<Vec<[i8; 3]>>,
+}
+
+impl Recording {
+  const MAGIC: &[u8; 4] = b"RGRP";
+  const VERSION: u8 = 3;
+  const HEADER_LEN: usize = 18;
+
+  fn quantize(input: PlayerInput) -> [i8; 3] {
+    [input.movement.x, input.movement.y, input.tilt]
+      .map(|axis| (axis.clamp(-1., 1.) * i8::MAX as f32).round() as i8)
+  }
+
+  fn dequantize(input: [i8; 3]) -> PlayerInput {
+    let [x, y, tilt] = input.map(|axis| axis as f32 / i8::MAX as f32);
+    PlayerInput { movement: Vec2::new(x, y), tilt }
+  }
+
+  /// Inputs rarely change from tick to tick, so they are stored as runs of
+  /// identical input.
+  fn encode(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(Self::HEADER_LEN);
+    bytes.extend_from_slice(Self::MAGIC);
+    bytes.push(Self::VERSION);
+    bytes.extend_from_slice(&self.seed.to_le_bytes());
+    bytes.extend_from_slice(&self.score.to_le_bytes());
+    bytes.push(self.players as u8);
+    for run in self.inputs.chunk_by(|a, b| a == b) {
+      for chunk in run.chunks(u16::MAX as usize) {
+        for &[x, y, tilt] in &chunk[0] {
+          bytes.extend_from_slice(&[x as u8, y as u8, tilt as u8]);
+        }
+        bytes.extend_from_slice(&(chunk.len() as u16).to_le_bytes());
+      }
+    }
+    bytes
+  }
+
+  fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+    if bytes.len() < Self::HEADER_LEN {
+      return Err(ReplayError::Truncated);
+    }
+    let (header, runs) = bytes.split_at(Self::HEADER_LEN);
+    if &header[0..4] != Self::MAGIC {
+      return Err(ReplayError::BadMagic);
+    }
+    if header[4] != Self::VERSION {
+      return Err(ReplayError::UnsupportedVersion(header[4]));
+    }
+    let players = header[17] as usize;
+    if !(1..=LocalPlayers::MAX).contains(&players) {
+      return Err(ReplayError::InvalidPlayerCount(players));
+    }
+    let run_len = players * 3 + 2;
+    if runs.len() % run_len != 0 {
+      return Err(ReplayError::Truncated);
+    }
+
+    let mut inputs = Vec::new();
+    for run in runs.chunks_exact(run_len) {
+      let (input, len) = run.split_at(players * 3);
+      let input: Vec<[i8; 3]> = input
+        .chunks_exact(3)
+        .map(|axes| [axes[0] as i8, axes[1] as i8, axes[2] as i8])
+        .collect();
+      let len = u16::from_le_bytes([len[0], len[1]]);
+      inputs.extend(std::iter::repeat_n(input, len as usize));
+    }
+
+    Ok(Self {
+      seed: u64::from_le_bytes(header[5..13].try_into().unwrap()),
+      score: u32::from_le_bytes(header[13..17].try_into().unwrap()),
+      players,
+      inputs,
+    })
+  }
+
+  fn load(path: &str) -> Result<Self, ReplayError> {
+    Self::decode(&std::fs::read(path)?)
+  }
+}
+
+/// Collects the input of the current run.
+#[derive(Resource, Default)]
+struct Recorder {
+  inputs: Vec<Vec<[i8; 3]>>,
+}
+
+/// Plays back a recording in place of the player's input.
+#[derive(Resource)]
+struct Replay {
+  recording: Recording,
+  /// The next tick of the recording to play.
+  tick: usize,
+  /// While set, the replay runs as fast as possible until it reaches this
+  /// tick.
+  seek_target: Option<usize>,
+}
+
+#[derive(Component)]
+struct ReplayHud;
+
+#[derive(Bundle)]
+struct ReplayHudBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+  node: Node,
+  hud: ReplayHud,
+}
+
+pub struct ReplayPlugin;
+
+impl ReplayPlugin {
+  /// Where the last run is saved when it ends.
+  const RECORDING_PATH: &str = "last_run.replay";
+
+  const FAST_FORWARD_SPEED: f32 = 4.;
+  const SEEK_SPEED: f32 = 64.;
+  /// How far the replay jumps with every press of a scrub key.
+  const SCRUB_STEP: Duration = Duration::from_secs(5);
+
+  /// The most time to simulate in one frame. This is raised while seeking, so
+  /// many ticks can run every frame.
+  const MAX_DELTA: Duration = Duration::from_millis(250);
+  const SEEK_MAX_DELTA: Duration = Duration::from_secs(2);
+
+  #[cfg(not(target_arch = "wasm32"))]
+  fn requested_replay() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+      if let Some(path) = arg.strip_prefix("--replay=") {
+        return Some(path.to_owned());
+      }
+      if arg == "--replay" {
+        return args.next();
+      }
+    }
+    None
+  }
+
+  /// Replays are read from and written to the file system, so they are not
+  /// available on the web.
+  #[cfg(target_arch = "wasm32")]
+  fn requested_replay() -> Option<String> {
+    None
+  }
+
+  fn start_recording(mut recorder: ResMut<Recorder>) {
+    recorder.inputs.clear();
+  }
+
+  /// Records this tick's input, rounding it to what the recording can store
+  /// so that the live run sees exactly what a replay will.
+  fn record_input(
+    mut recorder: ResMut<Recorder>,
+    players: Res<LocalPlayers>,
+    mut query: Query<(&PlayerId, &mut PlayerInput)>,
+  ) {
+    let mut inputs = vec![[0; 3]; players.count()];
+    for (&PlayerId(id), mut input) in &mut query {
+      let quantized = Recording::quantize(*input);
+      inputs[id] = quantized;
+      *input = Recording::dequantize(quantized);
+    }
+    recorder.inputs.push(inputs);
+  }
+
+  fn save_recording(
+    recorder: Res<Recorder>,
+    players: Res<LocalPlayers>,
+    rng: Res<GameRng>,
+    score: Res<ScoreResource>,
+  ) {
+    if cfg!(target_arch = "wasm32") {
+      return;
+    }
+    let recording = Recording {
+      seed: rng.seed(),
+      score: score.points,
+      players: players.count(),
+      inputs: recorder.inputs.clone(),
+    };
+    match std::fs::write(Self::RECORDING_PATH, recording.encode()) {
+      Ok(()) => info!("Saved replay to {}", Self::RECORDING_PATH),
+      Err(err) => error!("Could not save replay: {err}"),
+    }
+  }
+
+  fn start_replay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+  }
+
+  fn rewind(mut replay: ResMut<Replay>) {
+    replay.tick = 0;
+  }
+
+  fn feed_input(mut replay: ResMut<Replay>, mut query: Query<(&PlayerId, &mut PlayerInput)>) {
+    let inputs = replay.recording.inputs.get(replay.tick);
+    for (&PlayerId(id), mut input) in &mut query {
+      *input = inputs
+        .and_then(|inputs| inputs.get(id))
+        .map_or_else(PlayerInput::default, |&input| Recording::dequantize(input));
+    }
+    replay.tick += 1;
+  }
+
+  fn control_playback(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    fixed_time: Res<Time<Fixed>>,
+    state: Res<State<GameState>>,
+    mut replay: ResMut<Replay>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut time: ResMut<Time<Virtual>>,
+  ) {
+    let scrub_ticks = Self::SCRUB_STEP.div_duration_f32(fixed_time.timestep()) as usize;
+    let current = replay.seek_target.unwrap_or(replay.tick);
+    let target = if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+      Some(current.saturating_sub(scrub_ticks))
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+      Some((current + scrub_ticks).min(replay.recording.inputs.len()))
+    } else {
+      None
+    };
+
+    // The simulation can only run forwards, so seeking backwards replays the
+    // run from the start. Seeking forwards only makes progress while playing.
+    let can_seek = |target: &usize| *target < replay.tick || *state.get() == GameState::Playing;
+    if let Some(target) = target.filter(can_seek) {
+      if target < replay.tick {
+        next_state.set(GameState::Restarting);
+      }
+      replay.seek_target = Some(target);
+    }
+    if replay
+      .seek_target
+      .is_some_and(|target| replay.tick >= target)
+    {
+      replay.seek_target = None;
+    }
+
+    let (speed, max_delta) = if replay.seek_target.is_some() {
+      (Self::SEEK_SPEED, Self::SEEK_MAX_DELTA)
+    } else if keyboard_input.pressed(KeyCode::KeyF) {
+      (Self::FAST_FORWARD_SPEED, Self::MAX_DELTA)
+    } else {
+      (1., Self::MAX_DELTA)
+    };
+    time.set_relative_speed(speed);
+    time.set_max_delta(max_delta);
+  }
+
+  fn initialize_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(ReplayHudBundle {
+      text: Text::default(),
+      font: TextFont {
+        font: asset_server.load("fonts/Rubik-VariableFont_wght.ttf"),
+        font_size: 33.0,
+        ..default()
+      },
+      layout: TextLayout::new_with_justify(JustifyText::Right),
+      node: Node {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(5.0),
+        right: Val::Px(5.0),
+        ..default()
+      },
+      hud: ReplayHud,
+    });
+  }
+
+  fn update_hud(
+    mut hud: Single<&mut Text, With<ReplayHud>>,
+    fixed_time: Res<Time<Fixed>>,
+    state: Res<State<GameState>>,
+    replay: Res<Replay>,
+    score: Res<ScoreResource>,
+  ) {
+    let timestep = fixed_time.timestep();
+    let format_ticks = |ticks: usize| {
+      let secs = (timestep * ticks as u32).as_secs();
+      format!("{}:{:02}", secs / 60, secs % 60)
+    };
+    let recorded_score = replay.recording.score;
+    let status = match state.get() {
+      GameState::GameOver if score.points == recorded_score => "Score verified".to_owned(),
+      GameState::GameOver => format!(
+        "Score {} does not match recorded {recorded_score}",
+        score.points
+      ),
+      _ => "[F] fast forward  [Left/Right] scrub".to_owned(),
+    };
+    hud.0 = format!(
+      "Replay of seed {}  {} / {}\n{status}",
+      replay.recording.seed,
+      format_ticks(replay.tick),
+      format_ticks(replay.recording.inputs.len())
+    );
+  }
+
+  fn add_recorder(app: &mut App) {
+    app
+      .init_resource::<Recorder>()
+      .add_systems(OnEnter(InGame), Self::start_recording)
+      .add_systems(
+        FixedUpdate,
+        Self::record_input
+          .after(ReadPlayerInput)
+          .before(PlayerPlugin::move_player)
+          .run_if(GameStatePlugin::playing),
+      )
+      .add_systems(OnEnter(GameState::GameOver), Self::save_recording);
+  }
+
+  fn add_replay(app: &mut App, recording: Recording) {
+    app
+      .insert_resource(GameRng::new(Some(recording.seed)))
+      .insert_resource(LocalPlayers::replaying(recording.players))
+      .insert_resource(Replay { recording, tick: 0, seek_target: None })
+      .add_systems(
+        Startup,
+        Self::initialize_hud.after(WorldInitPlugin::world_init),
+      )
+      .add_systems(OnEnter(InGame), Self::rewind)
+      .add_systems(
+        FixedUpdate,
+        Self::feed_input
+          .after(ReadPlayerInput)
+          .before(PlayerPlugin::move_player)
+          .run_if(GameStatePlugin::playing),
+      )
+      .add_systems(
+        Update,
+        (
+          Self::start_replay.run_if(in_state(GameState::Title)),
+          Self::start_replay.run_if(in_state(GameState::WaveSummary)),
+          Self::control_playback,
+          Self::update_hud,
+        ),
+      );
+  }
+}
+
+impl Plugin for ReplayPlugin {
+  fn build(&self, app: &mut App) {
+    let recording = Self::requested_replay().and_then(|path| match Recording::load(&path) {
+      Ok(recording) => Some(recording),
+      Err(err) => {
+        error!("Could not load replay {path}: {err}");
+        None
+      }
+    });
+
+    match recording {
+      Some(recording) => Self::add_replay(app, recording),
+      None => Self::add_recorder(app),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::{
+    ecs::{
+      query::{Or, With},
+      system::RunSystemOnce,
+    },
+    input::{keyboard::KeyCode, ButtonInput},
+    state::state::State,
+  };
+  use bevy_world_space::position::Position;
+  use googletest::prelude::*;
+
+  use crate::{
+    game_state::GameState,
+    lobby::LocalPlayers,
+    npc::{Npc, NpcPlugin},
+    player::Player,
+    rng::GameRng,
+    score::ScoreResource,
+    test_harness::TestApp,
+  };
+
+  use super::{Recorder, Recording, Replay, ReplayError, ReplayPlugin};
+
+  /// The score, and where every umbrella and NPC is. The positions are
+  /// sorted, since the order entities are queried in may differ between apps.
+  fn final_state(app: &mut TestApp) -> (u32, Vec<(f32, f32)>) {
+    let world = app.world_mut();
+    let mut positions: Vec<_> = world
+      .query_filtered::<&Position, Or<(With<Player>, With<Npc>)>>()
+      .iter(world)
+      .map(|pos| (pos.pos.x.to_untyped(), pos.pos.y.to_untyped()))
+      .collect();
+    positions.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    (world.resource::<ScoreResource>().points, positions)
+  }
+
+  fn hold_keys(app: &mut TestApp, keys: &[KeyCode], duration: Duration) {
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    keyboard.release_all();
+    for &key in keys {
+      keyboard.press(key);
+    }
+    app.run_for(duration);
+  }
+
+  #[gtest]
+  fn recording_round_trips() {
+    let mut inputs = vec![vec![[0, 0, 0], [0, 0, 0]]; 70_000];
+    inputs.extend([
+      vec![[127, 0, 0], [0, 0, 0]],
+      vec![[127, 0, 0], [0, 0, 0]],
+      vec![[-127, 64, -30], [0, -127, 127]],
+      vec![[0, 0, 0], [0, 0, 0]],
+    ]);
+    let recording = Recording {
+      seed: 0xdead_beef_1234,
+      score: 42,
+      players: 2,
+      inputs,
+    };
+
+    let bytes = recording.encode();
+    expect_that!(bytes.len(), eq(Recording::HEADER_LEN + 5 * 8));
+    expect_that!(Recording::decode(&bytes).unwrap(), eq(&recording));
+  }
+
+  #[gtest]
+  fn truncated_recording_is_rejected() {
+    let recording = Recording {
+      seed: 7,
+      score: 0,
+      players: 1,
+      inputs: vec![vec![[1, 2, 3]]],
+    };
+    let bytes = recording.encode();
+
+    expect_that!(
+      Recording::decode(&bytes[..bytes.len() - 1]),
+      err(matches_pattern!(ReplayError::Truncated))
+    );
+  }
+
+  #[gtest]
+  fn replay_reproduces_the_recorded_run() {
+    let mut app = TestApp::new();
+    app.wait_until(NpcPlugin::roster_loaded);
+    ReplayPlugin::add_recorder(app.app_mut());
+    app.start_playing();
+    hold_keys(&mut app, &[KeyCode::KeyD], Duration::from_secs(3));
+    hold_keys(
+      &mut app,
+      &[KeyCode::KeyA, KeyCode::KeyQ],
+      Duration::from_secs(4),
+    );
+    hold_keys(
+      &mut app,
+      &[KeyCode::KeyW, KeyCode::KeyE],
+      Duration::from_secs(3),
+    );
+    assert_that!(
+      app.world().resource::<State<GameState>>().get(),
+      eq(&GameState::Playing)
+    );
+    let world = app.world();
+    let recording = Recording {
+      seed: world.resource::<GameRng>().seed(),
+      score: world.resource::<ScoreResource>().points,
+      players: world.resource::<LocalPlayers>().count(),
+      inputs: world.resource::<Recorder>().inputs.clone(),
+    };
+    let ticks = recording.inputs.len();
+    let recorded = final_state(&mut app);
+
+    let mut replay_app = TestApp::new();
+    replay_app.wait_until(NpcPlugin::roster_loaded);
+    ReplayPlugin::add_replay(
+      replay_app.app_mut(),
+      Recording::decode(&recording.encode()).unwrap(),
+    );
+    // The app has already started up, so the HUD has to be added by hand.
+    replay_app
+      .world_mut()
+      .run_system_once(ReplayPlugin::initialize_hud)
+      .unwrap();
+    while replay_app.world().resource::<Replay>().tick < ticks {
+      replay_app.step();
+    }
+
+    expect_that!(final_state(&mut replay_app), eq(&recorded));
+    expect_that!(
+      replay_app.world().resource::<ScoreResource>().points,
+      eq(replay_app.world().resource::<Replay>().recording.score)
+    );
+  }
+}
@@ -0,0 +1,171 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  ecs::{
+    schedule::{IntoSystemSetConfigs, SystemSet},
+    system::{ResMut, Resource},
+  },
+  state::state::OnEnter,
+};
+
+use crate::game_state::InGame;
+
+/// The source of all gameplay randomness. Every run starts from `seed`, so a
+/// run can be reproduced by passing the same seed back in with `--seed <n>`,
+/// or `?seed=<n>` on the web.
+#[derive(Resource)]
+pub struct GameRng {
+  seed: u64,
+  /// If set, every run replays the same seed rather than picking a new one.
+  fixed_seed: bool,
+  rng: fastrand::Rng,
+}
+
+impl GameRng {
+  pub fn new(seed: Option<u64>) -> Self {
+    let fixed_seed = seed.is_some();
+    let seed = seed.unwrap_or_else(|| fastrand::u64(..));
+    Self {
+      seed,
+      fixed_seed,
+      rng: fastrand::Rng::with_seed(seed),
+    }
+  }
+
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  fn reseed(&mut self) {
+    if !self.fixed_seed {
+      self.seed = fastrand::u64(..);
+    }
+    self.rng.seed(self.seed);
+  }
+}
+
+impl Deref for GameRng {
+  type Target = fastrand::Rng;
+
+  fn deref(&self) -> &fastrand::Rng {
+    &self.rng
+  }
+}
+
+impl DerefMut for GameRng {
+  fn deref_mut(&mut self) -> &mut fastrand::Rng {
+    &mut self.rng
+  }
+}
+
+/// Every system that draws from `GameRng` during a run belongs to one of
+/// these sets, which run in this order. Otherwise the scheduler could draw
+/// in a different order each run, and a seed would not reproduce the run.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawRandom {
+  Wind,
+  Rain,
+  Npcs,
+  Apples,
+  Lightning,
+}
+
+pub struct RngPlugin;
+
+impl RngPlugin {
+  #[cfg(not(target_arch = "wasm32"))]
+  fn requested_seed() -> Option<u64> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+      if let Some(seed) = arg.strip_prefix("--seed=") {
+        return seed.parse().ok();
+      }
+      if arg == "--seed" {
+        return args.next()?.parse().ok();
+      }
+    }
+    None
+  }
+
+  #[cfg(target_arch = "wasm32")]
+  fn requested_seed() -> Option<u64> {
+    let search = web_sys::window()?.location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search)
+      .ok()?
+      .get("seed")?
+      .parse()
+      .ok()
+  }
+
+  /// Restarts the RNG at the beginning of every run. Systems that draw from
+  /// the RNG when a run starts must run after this.
+  pub fn reseed_rng(mut rng: ResMut<GameRng>) {
+    rng.reseed();
+  }
+}
+
+impl Plugin for RngPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .insert_resource(GameRng::new(Self::requested_seed()))
+      .add_systems(OnEnter(InGame), Self::reseed_rng)
+      .configure_sets(
+        FixedUpdate,
+        (
+          DrawRandom::Wind,
+          DrawRandom::Rain,
+          DrawRandom::Npcs,
+          DrawRandom::Apples,
+          DrawRandom::Lightning,
+        )
+          .chain(),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::ecs::{component::Component, query::With};
+  use bevy_world_space::position::Position;
+  use googletest::prelude::*;
+
+  use crate::{
+    npc::{Npc, NpcPlugin},
+    rain::Rain,
+    test_harness::TestApp,
+  };
+
+  fn positions<C: Component>(app: &mut TestApp) -> Vec<(f32, f32)> {
+    let world = app.world_mut();
+    world
+      .query_filtered::<&Position, With<C>>()
+      .iter(world)
+      .map(|pos| (pos.pos.x.to_untyped(), pos.pos.y.to_untyped()))
+      .collect()
+  }
+
+  /// Plays ten seconds from the test seed, returning where every NPC and
+  /// raindrop ends up.
+  fn play_run() -> (Vec<(f32, f32)>, Vec<(f32, f32)>) {
+    let mut app = TestApp::new();
+    app
+      .wait_until(NpcPlugin::roster_loaded)
+      .start_playing()
+      .run_for(Duration::from_secs(10));
+    (positions::<Npc>(&mut app), positions::<Rain>(&mut app))
+  }
+
+  #[gtest]
+  fn the_same_seed_plays_the_same_run() {
+    let (npcs, rain) = play_run();
+    expect_that!(npcs, not(is_empty()));
+    expect_that!(rain, not(is_empty()));
+
+    let (replayed_npcs, replayed_rain) = play_run();
+    expect_that!(replayed_npcs, eq(&npcs));
+    expect_that!(replayed_rain, eq(&rain));
+  }
+}
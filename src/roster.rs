@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use bevy::{
+  asset::{io::Reader, Asset, AssetLoader, Handle, LoadContext},
+  image::Image,
+  reflect::TypePath,
+};
+use bevy_world_space::world_unit::WorldUnit;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// A single character as written in a roster file. `walk_frames` face right,
+/// and characters without `walk_left_frames` are mirrored when walking left.
+#[derive(Deserialize)]
+struct CharacterDescription {
+  name: String,
+  walk_frames: Vec<String>,
+  #[serde(default)]
+  walk_left_frames: Vec<String>,
+  wet_frame: String,
+  animation_period_ms: u64,
+  walk_speed: f32,
+  spawn_weight: f32,
+  image_width: u32,
+  image_height: u32,
+  width: f32,
+}
+
+#[derive(Deserialize)]
+struct RosterDescription {
+  characters: Vec<CharacterDescription>,
+}
+
+pub struct Character {
+  pub walk_sprites: Vec<Handle<Image>>,
+  pub walk_left_sprites: Option<Vec<Handle<Image>>>,
+  pub wet_sprite: Handle<Image>,
+  pub animation_period: Duration,
+  pub walk_speed: WorldUnit,
+  pub spawn_weight: f32,
+  pub image_width: u32,
+  pub width: WorldUnit,
+  pub height: WorldUnit,
+}
+
+/// The set of characters that NPCs may be spawned as, loaded from a
+/// `.roster.ron` file.
+#[derive(Asset, TypePath)]
+pub struct Roster {
+  characters: Vec<Character>,
+}
+
+impl Roster {
+  pub fn character(&self, idx: usize) -> &Character {
+    &self.characters[idx]
+  }
+
+  /// Chooses a random character, weighted by each character's spawn weight.
+  pub fn random_character(&self, rng: &mut fastrand::Rng) -> usize {
+    let total_weight: f32 = self.characters.iter().map(|c| c.spawn_weight).sum();
+    let mut target = rng.f32() * total_weight;
+    for (idx, character) in self.characters.iter().enumerate() {
+      if target < character.spawn_weight {
+        return idx;
+      }
+      target -= character.spawn_weight;
+    }
+    self.characters.len() - 1
+  }
+}
+
+#[derive(Debug, Error)]
+pub enum RosterLoaderError {
+  #[error("Could not read roster: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("Could not parse roster: {0}")]
+  Ron(#[from] ron::error::SpannedError),
+  #[error("Roster has no characters")]
+  Empty,
+  #[error("Character \"{0}\" has no walk frames")]
+  NoWalkFrames(String),
+  #[error("Character \"{0}\" has a negative spawn weight")]
+  NegativeSpawnWeight(String),
+}
+
+#[derive(Default)]
+pub struct RosterLoader;
+
+impl RosterLoader {
+  fn load_character(
+    description: CharacterDescription,
+    load_context: &mut LoadContext,
+  ) -> Result<Character, RosterLoaderError> {
+    if description.walk_frames.is_empty() {
+      return Err(RosterLoaderError::NoWalkFrames(description.name));
+    }
+    if description.spawn_weight < 0. {
+      return Err(RosterLoaderError::NegativeSpawnWeight(description.name));
+    }
+
+    let walk_left_sprites = (!description.walk_left_frames.is_empty()).then(|| {
+      description
+        .walk_left_frames
+        .into_iter()
+        .map(|path| load_context.load(path))
+        .collect()
+    });
+
+    let aspect_ratio = description.image_height as f32 / description.image_width as f32;
+    Ok(Character {
+      walk_sprites: description
+        .walk_frames
+        .into_iter()
+        .map(|path| load_context.load(path))
+        .collect(),
+      walk_left_sprites,
+      wet_sprite: load_context.load(description.wet_frame),
+      animation_period: Duration::from_millis(description.animation_period_ms),
+      walk_speed: WorldUnit::new(description.walk_speed),
+      spawn_weight: description.spawn_weight,
+      image_width: description.image_width,
+      width: WorldUnit::new(description.width),
+      height: WorldUnit::new(description.width * aspect_ratio),
+    })
+  }
+}
+
+impl AssetLoader for RosterLoader {
+  type Asset = Roster;
+  type Settings = ();
+  type Error = RosterLoaderError;
+
+  async fn load(
+    &self,
+    reader: &mut dyn Reader,
+    _settings: &(),
+    load_context: &mut LoadContext<'_>,
+  ) -> Result<Roster, RosterLoaderError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    let description: RosterDescription = ron::de::from_bytes(&bytes)?;
+    if description.characters.is_empty() {
+      return Err(RosterLoaderError::Empty);
+    }
+
+    let characters = description
+      .characters
+      .into_iter()
+      .map(|character| Self::load_character(character, load_context))
+      .collect::<Result<_, _>>()?;
+    Ok(Roster { characters })
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["roster.ron"]
+  }
+}
@@ -9,6 +9,7 @@ use bevy::{
     schedule::IntoSystemConfigs,
     system::{Commands, Res, ResMut, Resource, Single},
   },
+  state::state::OnEnter,
   text::{JustifyText, TextFont, TextLayout},
   ui::{widget::Text, Node, PositionType, Val},
   utils::default,
@@ -16,12 +17,35 @@ use bevy::{
 
 use bevy_world_space::world_init::WorldInitPlugin;
 
+use crate::{game_state::InGame, lobby::LocalPlayers, player::PlayerId};
+
+/// Earns the team a point, crediting it to `player` if anyone in particular
+/// earned it.
 #[derive(Event)]
-pub struct EarnPoint;
+pub struct EarnPoint {
+  pub player: Option<PlayerId>,
+}
 
 #[derive(Default, Resource)]
-struct ScoreResource {
-  points: u32,
+pub struct ScoreResource {
+  pub points: u32,
+  /// The points each player earned. These don't always add up to the team's
+  /// score, since some points aren't earned by anyone in particular.
+  by_player: [u32; LocalPlayers::MAX],
+}
+
+impl ScoreResource {
+  /// Each player's share of the score, or nothing when playing alone.
+  pub fn player_breakdown(&self, players: usize) -> Option<String> {
+    (players > 1).then(|| {
+      let scores: Vec<String> = self.by_player[..players]
+        .iter()
+        .enumerate()
+        .map(|(id, points)| format!("{}: {points}", PlayerId(id).label()))
+        .collect();
+      scores.join("  ")
+    })
+  }
 }
 
 #[derive(Component)]
@@ -59,11 +83,27 @@ impl ScorePlugin {
   }
 
   fn earn_points(mut points: EventReader<EarnPoint>, mut score: ResMut<ScoreResource>) {
-    score.points += points.read().count() as u32;
+    for point in points.read() {
+      score.points += 1;
+      if let Some(PlayerId(id)) = point.player {
+        score.by_player[id] += 1;
+      }
+    }
+  }
+
+  fn reset_score(mut score: ResMut<ScoreResource>) {
+    *score = ScoreResource::default();
   }
 
-  fn update_score(mut score_ui: Single<&mut Text, With<Score>>, score: Res<ScoreResource>) {
-    score_ui.0 = format!("Score: {}", score.points);
+  fn update_score(
+    mut score_ui: Single<&mut Text, With<Score>>,
+    score: Res<ScoreResource>,
+    players: Res<LocalPlayers>,
+  ) {
+    score_ui.0 = match score.player_breakdown(players.count()) {
+      Some(breakdown) => format!("Score: {}\n{breakdown}", score.points),
+      None => format!("Score: {}", score.points),
+    };
   }
 }
 
@@ -77,6 +117,7 @@ impl Plugin for ScorePlugin {
         ScorePlugin::initialize_ui.after(WorldInitPlugin::world_init),
       )
       .add_systems(FixedUpdate, ScorePlugin::earn_points)
+      .add_systems(OnEnter(InGame), ScorePlugin::reset_score)
       .add_systems(Update, ScorePlugin::update_score);
   }
 }
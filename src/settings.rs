@@ -0,0 +1,288 @@
+use bevy::{
+  app::{App, Plugin, Update},
+  asset::AssetServer,
+  color::Color,
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    query::Changed,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource},
+  },
+  hierarchy::{BuildChildren, ChildBuild},
+  input::{gamepad::Gamepad, keyboard::KeyCode, ButtonInput},
+  log::error,
+  state::{
+    app::AppExtStates,
+    condition::in_state,
+    state::{NextState, OnEnter, OnExit, States},
+    state_scoped::StateScoped,
+  },
+  text::{JustifyText, TextFont, TextLayout},
+  ui::{
+    widget::{Button, Text},
+    AlignItems, BackgroundColor, FlexDirection, FocusPolicy, GlobalZIndex, Interaction,
+    JustifyContent, Node, PositionType, UiRect, Val,
+  },
+  utils::default,
+};
+
+use crate::{
+  controls::{Action, Controls, Device},
+  game_state::GameState,
+};
+
+/// Whether the controls screen is open. This is separate from `GameState` so
+/// that it can be opened from the pause menu without ending the run.
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SettingsState {
+  #[default]
+  Closed,
+  Open,
+}
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+  Rebind(Action, Device),
+  Reset,
+  Back,
+}
+
+/// Marks text that shows what an action is bound to.
+#[derive(Component)]
+struct BindingLabel(Action, Device);
+
+/// The binding waiting for the player to press a key or button, if any.
+#[derive(Resource, Default)]
+struct Rebinding(Option<(Action, Device)>);
+
+#[derive(Bundle)]
+struct SettingsScreenBundle {
+  node: Node,
+  background: BackgroundColor,
+  focus: FocusPolicy,
+  z_index: GlobalZIndex,
+  scope: StateScoped<SettingsState>,
+}
+
+#[derive(Bundle)]
+struct SettingsButtonBundle {
+  button: Button,
+  node: Node,
+  background: BackgroundColor,
+  settings_button: SettingsButton,
+}
+
+#[derive(Bundle)]
+struct SettingsTextBundle {
+  text: Text,
+  font: TextFont,
+  layout: TextLayout,
+}
+
+pub struct SettingsPlugin;
+
+impl SettingsPlugin {
+  const OVERLAY_COLOR: Color = Color::srgba(0., 0., 0., 0.85);
+  const BUTTON_COLOR: Color = Color::srgba(0.2, 0.2, 0.3, 0.9);
+  const HOVERED_BUTTON_COLOR: Color = Color::srgba(0.35, 0.35, 0.5, 0.9);
+
+  const FONT_SIZE: f32 = 28.;
+
+  fn show_settings(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Rubik-VariableFont_wght.ttf");
+    let text = |text: &str, font_size: f32| SettingsTextBundle {
+      text: Text::new(text),
+      font: TextFont {
+        font: font.clone(),
+        font_size,
+        ..default()
+      },
+      layout: TextLayout::new_with_justify(JustifyText::Center),
+    };
+    let button = |width: f32, settings_button: SettingsButton| SettingsButtonBundle {
+      button: Button,
+      node: Node {
+        width: Val::Px(width),
+        padding: UiRect::all(Val::Px(6.)),
+        justify_content: JustifyContent::Center,
+        ..default()
+      },
+      background: BackgroundColor(Self::BUTTON_COLOR),
+      settings_button,
+    };
+
+    commands
+      .spawn(SettingsScreenBundle {
+        node: Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          height: Val::Percent(100.),
+          flex_direction: FlexDirection::Column,
+          align_items: AlignItems::Center,
+          justify_content: JustifyContent::Center,
+          row_gap: Val::Px(8.),
+          ..default()
+        },
+        background: BackgroundColor(Self::OVERLAY_COLOR),
+        focus: FocusPolicy::Block,
+        z_index: GlobalZIndex(1),
+        scope: StateScoped(SettingsState::Open),
+      })
+      .with_children(|parent| {
+        parent.spawn(text("Controls", 50.));
+
+        for action in Action::ALL {
+          parent
+            .spawn(Node {
+              column_gap: Val::Px(10.),
+              align_items: AlignItems::Center,
+              ..default()
+            })
+            .with_children(|row| {
+              row
+                .spawn(Node { width: Val::Px(180.), ..default() })
+                .with_child(text(action.label(), Self::FONT_SIZE));
+              for device in [Device::Keyboard, Device::Gamepad] {
+                row
+                  .spawn(button(260., SettingsButton::Rebind(action, device)))
+                  .with_child((text("", Self::FONT_SIZE), BindingLabel(action, device)));
+              }
+            });
+        }
+
+        parent
+          .spawn(Node {
+            column_gap: Val::Px(10.),
+            margin: UiRect::top(Val::Px(12.)),
+            ..default()
+          })
+          .with_children(|row| {
+            row
+              .spawn(button(260., SettingsButton::Reset))
+              .with_child(text("Reset defaults", Self::FONT_SIZE));
+            row
+              .spawn(button(260., SettingsButton::Back))
+              .with_child(text("Back", Self::FONT_SIZE));
+          });
+      });
+  }
+
+  fn open_from_title(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_settings: ResMut<NextState<SettingsState>>,
+  ) {
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+      next_settings.set(SettingsState::Open);
+    }
+  }
+
+  fn handle_buttons(
+    mut query: Query<(&Interaction, &SettingsButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut controls: ResMut<Controls>,
+    mut next_settings: ResMut<NextState<SettingsState>>,
+  ) {
+    for (interaction, settings_button, mut background) in &mut query {
+      match interaction {
+        Interaction::Pressed => match *settings_button {
+          SettingsButton::Rebind(action, device) => rebinding.0 = Some((action, device)),
+          SettingsButton::Reset => *controls = Controls::default(),
+          SettingsButton::Back => next_settings.set(SettingsState::Closed),
+        },
+        Interaction::Hovered => background.0 = Self::HOVERED_BUTTON_COLOR,
+        Interaction::None => background.0 = Self::BUTTON_COLOR,
+      }
+    }
+  }
+
+  /// Binds the next key or gamepad button pressed to the action being
+  /// rebound, even one bound to `Action::Back`. Otherwise `Action::Back`
+  /// closes the screen, or cancels rebinding from the other device.
+  fn capture_binding(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut controls: ResMut<Controls>,
+    mut next_settings: ResMut<NextState<SettingsState>>,
+  ) {
+    let back = controls.just_pressed(Action::Back, &keyboard_input, &gamepads);
+    let Some((action, device)) = rebinding.0 else {
+      if back {
+        next_settings.set(SettingsState::Closed);
+      }
+      return;
+    };
+
+    match device {
+      Device::Keyboard => {
+        if let Some(&key) = keyboard_input.get_just_pressed().next() {
+          controls.rebind_key(action, key);
+          rebinding.0 = None;
+        }
+      }
+      Device::Gamepad => {
+        if let Some(&button) = gamepads
+          .iter()
+          .find_map(|gamepad| gamepad.get_just_pressed().next())
+        {
+          controls.rebind_button(action, button);
+          rebinding.0 = None;
+        }
+      }
+    }
+    if back {
+      rebinding.0 = None;
+    }
+  }
+
+  fn update_labels(
+    controls: Res<Controls>,
+    rebinding: Res<Rebinding>,
+    mut query: Query<(&mut Text, &BindingLabel)>,
+  ) {
+    for (mut text, &BindingLabel(action, device)) in &mut query {
+      text.0 = match rebinding.0 {
+        Some(rebinding) if rebinding == (action, device) => match device {
+          Device::Keyboard => "Press a key...".to_owned(),
+          Device::Gamepad => "Press a button...".to_owned(),
+        },
+        _ => controls.describe(action, device),
+      };
+    }
+  }
+
+  fn close_settings(mut rebinding: ResMut<Rebinding>, controls: Res<Controls>) {
+    rebinding.0 = None;
+    if let Err(err) = controls.save() {
+      error!("{err}");
+    }
+  }
+}
+
+impl Plugin for SettingsPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_state::<SettingsState>()
+      .enable_state_scoped_entities::<SettingsState>()
+      .init_resource::<Rebinding>()
+      .add_systems(OnEnter(SettingsState::Open), Self::show_settings)
+      .add_systems(OnExit(SettingsState::Open), Self::close_settings)
+      .add_systems(
+        Update,
+        Self::open_from_title
+          .run_if(in_state(GameState::Title))
+          .run_if(in_state(SettingsState::Closed)),
+      )
+      .add_systems(
+        Update,
+        (
+          Self::handle_buttons,
+          Self::capture_binding,
+          Self::update_labels,
+        )
+          .chain()
+          .run_if(in_state(SettingsState::Open)),
+      );
+  }
+}
@@ -6,9 +6,8 @@ use bevy::{
   ecs::{
     bundle::Bundle,
     component::Component,
-    query::{With, Without},
     schedule::IntoSystemConfigs,
-    system::{Commands, Query, Res, Resource, Single},
+    system::{Commands, Res, Resource, Single},
   },
   image::Image,
   sprite::Sprite,
@@ -20,7 +19,7 @@ use bevy_world_space::{
   world_unit::{WorldUnit, WorldVec2},
 };
 
-use crate::{movable::MoveComponent, rain::Rain};
+use crate::collider::{Collider, Shape};
 
 #[derive(Component)]
 struct Shack {
@@ -52,6 +51,7 @@ struct ShackBundle {
   sprite: Sprite,
   pos: Position,
   shack: Shack,
+  collider: Collider,
 }
 
 #[derive(Resource)]
@@ -69,8 +69,25 @@ impl ShackPlugin {
 
   const Z_IDX: f32 = 2.;
 
+  /// How far the slope of the roof cuts into the top left corner.
+  const ROOF_OVERHANG: WorldUnit = WorldUnit::new(3.);
+
   const RAIN_RESTITUTION: f32 = 0.3;
 
+  /// A box with the top left corner cut off along the slope of the roof, so
+  /// rain slides off the roof.
+  fn outline() -> Shape {
+    let half_width = Self::WIDTH / 2.;
+    let half_height = Self::HEIGHT / 2.;
+    Shape::ConvexPolygon(vec![
+      WorldVec2::new(-half_width, -half_height),
+      WorldVec2::new(half_width, -half_height),
+      WorldVec2::new(half_width, half_height),
+      WorldVec2::new(-half_width + Self::ROOF_OVERHANG, half_height),
+      WorldVec2::new(-half_width, half_height - Self::ROOF_OVERHANG),
+    ])
+  }
+
   fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
     let shack_sprites = [1, 2, 3, 2].map(|idx| asset_server.load(format!("shack/shack_{idx}.png")));
     commands.insert_resource(ShackAssets { shack_sprites });
@@ -89,6 +106,7 @@ impl ShackPlugin {
         Self::Z_IDX,
       ),
       shack: Shack::new(),
+      collider: Collider::solid(Self::outline(), Self::RAIN_RESTITUTION, 0.),
     });
   }
 
@@ -100,31 +118,6 @@ impl ShackPlugin {
     let (mut shack, mut sprite) = shack.into_inner();
     shack.tick(time.delta(), &shack_assets, &mut sprite);
   }
-
-  fn handle_rain_collisions(
-    shack: Single<&Position, With<Shack>>,
-    mut rain_query: Query<(&Position, &mut MoveComponent), (With<Rain>, Without<Shack>)>,
-  ) {
-    let shack_pos = shack.into_inner().pos;
-    for (rain_pos, mut rain_vel) in &mut rain_query {
-      let rain_pos = rain_pos.pos;
-      let diff = rain_pos - shack_pos;
-
-      let tl_corner = shack_pos + WorldVec2::new(-Self::WIDTH / 2., Self::HEIGHT / 2.);
-      let from_tl_corner = rain_pos - tl_corner;
-
-      if diff.x.abs() <= Self::WIDTH / 2. && diff.y.abs() <= Self::HEIGHT / 2. {
-        if from_tl_corner.y < -from_tl_corner.x {
-          if rain_vel.delta.x > WorldUnit::ZERO {
-            rain_vel.delta.x = -rain_vel.delta.x * Self::RAIN_RESTITUTION;
-          }
-        } else if rain_vel.delta.y < WorldUnit::ZERO {
-          rain_vel.delta.y = -rain_vel.delta.y * Self::RAIN_RESTITUTION;
-          rain_vel.delta.x += WorldUnit::new(0.1);
-        }
-      }
-    }
-  }
 }
 
 impl Plugin for ShackPlugin {
@@ -138,6 +131,6 @@ impl Plugin for ShackPlugin {
         )
           .chain(),
       )
-      .add_systems(FixedUpdate, (Self::tick, Self::handle_rain_collisions));
+      .add_systems(FixedUpdate, Self::tick);
   }
 }
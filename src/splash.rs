@@ -0,0 +1,206 @@
+use std::{f32::consts::FRAC_PI_4, time::Duration};
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin, Startup},
+  asset::{AssetServer, Handle},
+  color::{Alpha, Color},
+  ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::{Event, EventReader},
+    query::With,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource},
+  },
+  image::Image,
+  math::Vec2,
+  sprite::Sprite,
+  state::state_scoped::StateScoped,
+  time::{Time, Timer, TimerMode},
+};
+use bevy_world_space::{
+  position::Position,
+  world_unit::{WorldUnit, WorldVec2},
+};
+
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  gravity::GravityComponent,
+  movable::MoveComponent,
+  npc::NpcPlugin,
+  rain::RainPlugin,
+};
+
+#[derive(Event)]
+pub struct Splash {
+  pub pos: WorldVec2,
+  pub normal: WorldVec2,
+  pub speed: WorldUnit,
+}
+
+#[derive(Component)]
+#[require(MoveComponent, GravityComponent)]
+struct Droplet {
+  lifetime: Timer,
+}
+
+#[derive(Bundle)]
+struct DropletBundle {
+  sprite: Sprite,
+  pos: Position,
+  droplet: Droplet,
+  move_component: MoveComponent,
+  scope: StateScoped<InGame>,
+}
+
+impl DropletBundle {
+  const IMG_WIDTH: u32 = 233;
+
+  const WIDTH: WorldUnit = WorldUnit::new(0.15);
+
+  const LIFETIME: Duration = Duration::from_millis(350);
+
+  const Z_IDX: f32 = 0.5;
+
+  fn new(image: Handle<Image>, pos: WorldVec2, delta: WorldVec2) -> Self {
+    Self {
+      sprite: Sprite::from_image(image),
+      pos: Position::new(pos, Self::WIDTH, Self::IMG_WIDTH, Self::Z_IDX),
+      droplet: Droplet {
+        lifetime: Timer::new(Self::LIFETIME, TimerMode::Once),
+      },
+      move_component: MoveComponent { delta },
+      scope: StateScoped(InGame),
+    }
+  }
+}
+
+#[derive(Resource)]
+struct SplashResources {
+  droplet_image: Handle<Image>,
+  /// Splashes are only for show, so they don't draw from `GameRng` and
+  /// disturb the gameplay.
+  rng: fastrand::Rng,
+}
+
+pub struct SplashPlugin;
+
+impl SplashPlugin {
+  const MIN_SPEED: WorldUnit = WorldUnit::new(4.);
+
+  const MAX_DROPLETS_PER_SPLASH: usize = 4;
+
+  /// Heavy rain could otherwise fill the screen with droplets.
+  const MAX_DROPLETS: usize = 512;
+
+  const SPRAY_SPEED: f32 = 0.3;
+  const SPRAY_ANGLE: f32 = FRAC_PI_4;
+
+  fn initialize_plugin(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SplashResources {
+      droplet_image: asset_server.load("raindrop/raindrop.png"),
+      rng: fastrand::Rng::new(),
+    });
+  }
+
+  fn spawn_droplets(
+    mut commands: Commands,
+    mut splashes: EventReader<Splash>,
+    mut resources: ResMut<SplashResources>,
+    droplets: Query<(), With<Droplet>>,
+  ) {
+    let mut count = droplets.iter().count();
+    let SplashResources { droplet_image, rng } = &mut *resources;
+    for splash in splashes.read() {
+      if splash.speed < Self::MIN_SPEED {
+        continue;
+      }
+      let droplets = (splash.speed / Self::MIN_SPEED.to_untyped())
+        .to_untyped()
+        .min(Self::MAX_DROPLETS_PER_SPLASH as f32) as usize;
+      for _ in 0..droplets.min(Self::MAX_DROPLETS.saturating_sub(count)) {
+        let angle = (rng.f32() * 2. - 1.) * Self::SPRAY_ANGLE;
+        let direction = Vec2::from_angle(angle).rotate(splash.normal.to_untyped());
+        let speed = splash.speed * Self::SPRAY_SPEED * (0.5 + rng.f32() / 2.);
+        commands.spawn(DropletBundle::new(
+          droplet_image.clone_weak(),
+          splash.pos,
+          WorldVec2::new(speed * direction.x, speed * direction.y),
+        ));
+        count += 1;
+      }
+    }
+  }
+
+  fn fade_droplets(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Droplet, &mut Sprite)>,
+  ) {
+    for (entity, mut droplet, mut sprite) in &mut query {
+      droplet.lifetime.tick(time.delta());
+      if droplet.lifetime.finished() {
+        commands.entity(entity).despawn();
+      } else {
+        sprite.color = Color::WHITE.with_alpha(droplet.lifetime.fraction_remaining());
+      }
+    }
+  }
+}
+
+impl Plugin for SplashPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_event::<Splash>()
+      .add_systems(Startup, Self::initialize_plugin)
+      .add_systems(
+        FixedUpdate,
+        (
+          Self::spawn_droplets
+            .after(NpcPlugin::soak_npcs)
+            .after(RainPlugin::recycle_raindrops),
+          Self::fade_droplets,
+        )
+          .run_if(GameStatePlugin::playing),
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::ecs::query::With;
+  use bevy_world_space::world_unit::{WorldUnit, WorldVec2};
+  use googletest::prelude::*;
+
+  use crate::test_harness::TestApp;
+
+  use super::{Droplet, DropletBundle, Splash};
+
+  fn droplet_count(app: &mut TestApp) -> usize {
+    let world = app.world_mut();
+    world
+      .query_filtered::<(), With<Droplet>>()
+      .iter(world)
+      .count()
+  }
+
+  #[gtest]
+  fn hard_hits_splash_and_the_droplets_fade_away() {
+    let mut app = TestApp::new();
+    app.start_playing();
+
+    for speed in [1., 30.] {
+      app.world_mut().send_event(Splash {
+        pos: WorldVec2::ZERO,
+        normal: WorldVec2::Y,
+        speed: WorldUnit::new(speed),
+      });
+    }
+    app.step();
+    expect_that!(droplet_count(&mut app), eq(4));
+
+    app.run_for(DropletBundle::LIFETIME);
+    expect_that!(droplet_count(&mut app), eq(0));
+  }
+}
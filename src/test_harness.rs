@@ -0,0 +1,190 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{
+  app::{App, Last},
+  asset::{AssetApp, AssetPlugin, Handle},
+  ecs::{
+    entity::Entity,
+    event::{Event, EventReader},
+    system::{ResMut, Resource},
+    world::World,
+  },
+  image::Image,
+  input::InputPlugin,
+  state::{app::StatesPlugin, state::NextState},
+  text::Font,
+  time::{Fixed, Time, TimeUpdateStrategy},
+  MinimalPlugins,
+};
+use bevy_world_space::world_unit::WorldVec2;
+
+use crate::{
+  apple::ApplePlugin,
+  collider::CollisionPlugin,
+  controls::Controls,
+  game_state::{GameState, GameStatePlugin},
+  gravity::GravityPlugin,
+  lightning::LightningPlugin,
+  lives::LivesPlugin,
+  lobby::LobbyPlugin,
+  movable::{MoveComponent, MovePlugin},
+  npc::NpcPlugin,
+  player::PlayerPlugin,
+  puddle::PuddlePlugin,
+  rain::{RainBundle, RainKind, RainPlugin},
+  rng::{GameRng, RngPlugin},
+  score::ScorePlugin,
+  settings::SettingsPlugin,
+  shack::ShackPlugin,
+  splash::SplashPlugin,
+  wave::WavePlugin,
+  wind::WindPlugin,
+};
+
+#[derive(Resource)]
+struct EventCount<E: Event> {
+  count: usize,
+  _event: PhantomData<E>,
+}
+
+/// A headless app running the gameplay plugins, with no window or renderer.
+/// Every call to `step` advances time by exactly one fixed timestep.
+pub struct TestApp {
+  app: App,
+}
+
+impl TestApp {
+  /// How many frames to wait for assets to load before giving up.
+  const MAX_LOAD_FRAMES: u32 = 1000;
+
+  /// Every test app starts from the same seed, so tests are reproducible.
+  const SEED: u64 = 0;
+
+  pub fn new() -> Self {
+    let mut app = App::new();
+    app
+      .add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        InputPlugin,
+        StatesPlugin,
+      ))
+      .init_asset::<Image>()
+      .init_asset::<Font>()
+      .init_resource::<Controls>()
+      .add_plugins((
+        GameStatePlugin,
+        LivesPlugin,
+        WavePlugin,
+        WindPlugin,
+        LightningPlugin,
+        RngPlugin,
+        SettingsPlugin,
+        LobbyPlugin,
+      ))
+      .add_plugins((
+        MovePlugin,
+        GravityPlugin,
+        CollisionPlugin,
+        RainPlugin,
+        SplashPlugin,
+        PuddlePlugin,
+        NpcPlugin,
+        PlayerPlugin,
+        ShackPlugin,
+        ScorePlugin,
+        ApplePlugin,
+      ));
+
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app
+      .insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+      .insert_resource(GameRng::new(Some(Self::SEED)));
+
+    let mut test_app = Self { app };
+    test_app.step();
+    test_app
+  }
+
+  /// Starts a run, as if the player had left the title screen.
+  pub fn start_playing(&mut self) -> &mut Self {
+    self
+      .world_mut()
+      .resource_mut::<NextState<GameState>>()
+      .set(GameState::Playing);
+    self.step()
+  }
+
+  pub fn world(&self) -> &World {
+    self.app.world()
+  }
+
+  pub fn world_mut(&mut self) -> &mut World {
+    self.app.world_mut()
+  }
+
+  /// The app itself, for tests that add systems of their own.
+  pub fn app_mut(&mut self) -> &mut App {
+    &mut self.app
+  }
+
+  pub fn step(&mut self) -> &mut Self {
+    self.app.update();
+    self
+  }
+
+  pub fn run_for(&mut self, duration: Duration) -> &mut Self {
+    let timestep = self.world().resource::<Time<Fixed>>().timestep();
+    let steps = duration.div_duration_f32(timestep).ceil() as u32;
+    for _ in 0..steps {
+      self.step();
+    }
+    self
+  }
+
+  /// Steps until `loaded` returns true, panicking if it takes too long.
+  pub fn wait_until(&mut self, mut loaded: impl FnMut(&World) -> bool) -> &mut Self {
+    for _ in 0..Self::MAX_LOAD_FRAMES {
+      if loaded(self.world()) {
+        return self;
+      }
+      std::thread::sleep(Duration::from_millis(1));
+      self.step();
+    }
+    panic!("Timed out waiting for assets to load");
+  }
+
+  /// Spawns a drop of `kind` at `pos`, already moving with `delta`.
+  pub fn spawn_drop(&mut self, pos: WorldVec2, kind: RainKind, delta: WorldVec2) -> Entity {
+    self
+      .world_mut()
+      .spawn((
+        RainBundle::new(Handle::default(), pos, kind),
+        MoveComponent { delta },
+      ))
+      .id()
+  }
+
+  pub fn velocity(&self, entity: Entity) -> WorldVec2 {
+    self.world().get::<MoveComponent>(entity).unwrap().delta
+  }
+
+  /// Starts counting events of type `E`, which can be read back with
+  /// `event_count`.
+  pub fn track_events<E: Event>(&mut self) -> &mut Self {
+    self
+      .app
+      .insert_resource(EventCount::<E> { count: 0, _event: PhantomData })
+      .add_systems(
+        Last,
+        |mut events: EventReader<E>, mut count: ResMut<EventCount<E>>| {
+          count.count += events.read().count();
+        },
+      );
+    self
+  }
+
+  pub fn event_count<E: Event>(&self) -> usize {
+    self.world().resource::<EventCount<E>>().count
+  }
+}
@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  asset::AssetServer,
+  ecs::{
+    event::EventReader,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Res, ResMut, Resource},
+  },
+  state::state::{NextState, OnEnter, OnExit},
+  time::{Time, Timer, TimerMode},
+};
+use bevy_world_space::world_unit::WorldUnit;
+
+use crate::{
+  controls::{Action, Controls, Device},
+  game_state::{GameState, GameStatePlugin, InGame},
+  lives::LoseLife,
+  rain::RainKind,
+  rain_pattern::RainPattern,
+  score::ScoreResource,
+};
+
+/// Difficulty parameters for a single wave.
+#[derive(Clone, Copy)]
+pub struct WaveSettings {
+  /// The average time between raindrops.
+  pub rain_period: Duration,
+  /// How far the rain rate swings above and below its average, as a
+  /// fraction of the average.
+  pub rain_intensity_variation: f32,
+  /// Waves cycle through `WaveSettings::RAIN_PATTERNS` rather than
+  /// interpolating this.
+  pub rain_pattern: RainPattern,
+  pub npc_spawn_period: Duration,
+  /// NPC walk speeds are scaled by a random factor within this range.
+  pub min_npc_speed_scale: f32,
+  pub max_npc_speed_scale: f32,
+  pub gravity: WorldUnit,
+  /// The strongest gust of wind that may blow, in either direction.
+  pub max_wind_speed: WorldUnit,
+  /// The chances of each drop being heavy rain, hail or snow rather than an
+  /// ordinary raindrop.
+  pub heavy_chance: f32,
+  pub hail_chance: f32,
+  pub snow_chance: f32,
+  /// The average number of lightning strikes each second.
+  pub lightning_rate: f32,
+  pub duration: Duration,
+}
+
+impl WaveSettings {
+  const FIRST: Self = Self {
+    rain_period: Duration::from_millis(200),
+    rain_intensity_variation: 0.2,
+    rain_pattern: RainPattern::Drizzle,
+    npc_spawn_period: Duration::from_secs(5),
+    min_npc_speed_scale: 1.,
+    max_npc_speed_scale: 1.,
+    gravity: WorldUnit::new(16.),
+    max_wind_speed: WorldUnit::new(2.),
+    heavy_chance: 0.,
+    hail_chance: 0.,
+    snow_chance: 0.,
+    lightning_rate: 0.,
+    duration: Duration::from_secs(30),
+  };
+
+  const HARDEST: Self = Self {
+    rain_period: Duration::from_millis(50),
+    rain_intensity_variation: 0.8,
+    rain_pattern: RainPattern::Drizzle,
+    npc_spawn_period: Duration::from_secs(2),
+    min_npc_speed_scale: 0.8,
+    max_npc_speed_scale: 1.8,
+    gravity: WorldUnit::new(28.),
+    max_wind_speed: WorldUnit::new(10.),
+    heavy_chance: 0.2,
+    hail_chance: 0.1,
+    snow_chance: 0.1,
+    lightning_rate: 0.1,
+    duration: Duration::from_secs(60),
+  };
+
+  /// The wave at which difficulty stops increasing.
+  const HARDEST_WAVE: u32 = 10;
+
+  const RAIN_PATTERNS: [RainPattern; 4] = [
+    RainPattern::Drizzle,
+    RainPattern::Clouds { count: 2, width: 0.5, drift_speed: 0.15 },
+    RainPattern::Curtain { width: 0.6, sweep_period: 12. },
+    RainPattern::Bursts { drops_per_burst: 8, spread: 0.25 },
+  ];
+
+  /// Interpolates between the first and hardest waves.
+  pub fn for_wave(wave: u32) -> Self {
+    let t = ((wave.max(1) - 1) as f32 / (Self::HARDEST_WAVE - 1) as f32).min(1.);
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    let lerp_duration =
+      |a: Duration, b: Duration| Duration::from_secs_f32(lerp(a.as_secs_f32(), b.as_secs_f32()));
+
+    Self {
+      rain_period: lerp_duration(Self::FIRST.rain_period, Self::HARDEST.rain_period),
+      rain_intensity_variation: lerp(
+        Self::FIRST.rain_intensity_variation,
+        Self::HARDEST.rain_intensity_variation,
+      ),
+      rain_pattern: Self::RAIN_PATTERNS[(wave.max(1) - 1) as usize % Self::RAIN_PATTERNS.len()],
+      npc_spawn_period: lerp_duration(Self::FIRST.npc_spawn_period, Self::HARDEST.npc_spawn_period),
+      min_npc_speed_scale: lerp(
+        Self::FIRST.min_npc_speed_scale,
+        Self::HARDEST.min_npc_speed_scale,
+      ),
+      max_npc_speed_scale: lerp(
+        Self::FIRST.max_npc_speed_scale,
+        Self::HARDEST.max_npc_speed_scale,
+      ),
+      gravity: WorldUnit::new(lerp(
+        Self::FIRST.gravity.to_untyped(),
+        Self::HARDEST.gravity.to_untyped(),
+      )),
+      max_wind_speed: WorldUnit::new(lerp(
+        Self::FIRST.max_wind_speed.to_untyped(),
+        Self::HARDEST.max_wind_speed.to_untyped(),
+      )),
+      heavy_chance: lerp(Self::FIRST.heavy_chance, Self::HARDEST.heavy_chance),
+      hail_chance: lerp(Self::FIRST.hail_chance, Self::HARDEST.hail_chance),
+      snow_chance: lerp(Self::FIRST.snow_chance, Self::HARDEST.snow_chance),
+      lightning_rate: lerp(Self::FIRST.lightning_rate, Self::HARDEST.lightning_rate),
+      duration: lerp_duration(Self::FIRST.duration, Self::HARDEST.duration),
+    }
+  }
+
+  pub fn random_npc_speed_scale(&self, rng: &mut fastrand::Rng) -> f32 {
+    self.min_npc_speed_scale + (self.max_npc_speed_scale - self.min_npc_speed_scale) * rng.f32()
+  }
+
+  pub fn random_rain_kind(&self, rng: &mut fastrand::Rng) -> RainKind {
+    let roll = rng.f32();
+    if roll < self.heavy_chance {
+      RainKind::Heavy
+    } else if roll < self.heavy_chance + self.hail_chance {
+      RainKind::Hail
+    } else if roll < self.heavy_chance + self.hail_chance + self.snow_chance {
+      RainKind::Snow
+    } else {
+      RainKind::Drop
+    }
+  }
+}
+
+#[derive(Resource)]
+pub struct Wave {
+  number: u32,
+  settings: WaveSettings,
+  timer: Timer,
+  starting_points: u32,
+  soaked: u32,
+}
+
+impl Wave {
+  fn new(number: u32, starting_points: u32) -> Self {
+    let settings = WaveSettings::for_wave(number);
+    Self {
+      number,
+      settings,
+      timer: Timer::new(settings.duration, TimerMode::Once),
+      starting_points,
+      soaked: 0,
+    }
+  }
+
+  pub fn settings(&self) -> &WaveSettings {
+    &self.settings
+  }
+}
+
+pub struct WavePlugin;
+
+impl WavePlugin {
+  fn reset_waves(mut wave: ResMut<Wave>) {
+    *wave = Wave::new(1, 0);
+  }
+
+  fn tick_wave(
+    time: Res<Time>,
+    mut lost: EventReader<LoseLife>,
+    mut wave: ResMut<Wave>,
+    mut next_state: ResMut<NextState<GameState>>,
+  ) {
+    wave.soaked += lost.read().count() as u32;
+    if wave.timer.tick(time.delta()).just_finished() {
+      next_state.set(GameState::WaveSummary);
+    }
+  }
+
+  fn show_summary(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    wave: Res<Wave>,
+    score: Res<ScoreResource>,
+    controls: Res<Controls>,
+  ) {
+    GameStatePlugin::spawn_screen(
+      &mut commands,
+      &asset_server,
+      GameState::WaveSummary,
+      format!(
+        "Wave {} complete\nPoints: {}\nSoaked: {}\nPress {} to continue",
+        wave.number,
+        score.points - wave.starting_points,
+        wave.soaked,
+        controls.describe(Action::Confirm, Device::Keyboard)
+      ),
+    );
+  }
+
+  fn next_wave(mut wave: ResMut<Wave>, score: Res<ScoreResource>) {
+    *wave = Wave::new(wave.number + 1, score.points);
+  }
+}
+
+impl Plugin for WavePlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .insert_resource(Wave::new(1, 0))
+      .add_systems(OnEnter(InGame), Self::reset_waves)
+      .add_systems(
+        FixedUpdate,
+        Self::tick_wave.run_if(GameStatePlugin::playing),
+      )
+      .add_systems(OnEnter(GameState::WaveSummary), Self::show_summary)
+      .add_systems(OnExit(GameState::WaveSummary), Self::next_wave);
+  }
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use bevy::{
+  app::{App, FixedUpdate, Plugin},
+  ecs::{
+    query::With,
+    schedule::IntoSystemConfigs,
+    system::{Query, Res, ResMut, Resource},
+  },
+  state::state::OnEnter,
+  time::{Time, Timer, TimerMode},
+};
+use bevy_world_space::world_unit::WorldUnit;
+
+use crate::{
+  game_state::{GameStatePlugin, InGame},
+  movable::{MoveComponent, MovePlugin},
+  rain::{InPlay, Rain},
+  rng::{DrawRandom, GameRng},
+  wave::Wave,
+};
+
+/// Horizontal wind that blows the rain around. The wind eases towards a
+/// target speed, which changes with every gust.
+#[derive(Resource)]
+pub struct Wind {
+  speed: WorldUnit,
+  target_speed: WorldUnit,
+  gust_timer: Timer,
+}
+
+impl Wind {
+  /// Roughly how long a raindrop takes to fall from the top of the screen to
+  /// the bottom, used to decide how far upwind to spawn drops.
+  const FALL_TIME: f32 = 1.5;
+
+  /// How quickly the wind speed approaches the target speed, per second.
+  const EASING: f32 = 0.8;
+
+  const MIN_GUST_PERIOD: f32 = 2.;
+  const MAX_GUST_PERIOD: f32 = 6.;
+
+  fn new() -> Self {
+    Self {
+      speed: WorldUnit::ZERO,
+      target_speed: WorldUnit::ZERO,
+      gust_timer: Timer::new(Duration::ZERO, TimerMode::Once),
+    }
+  }
+
+  pub fn speed(&self) -> WorldUnit {
+    self.speed
+  }
+
+  /// How far upwind raindrops should spawn so that they land roughly where
+  /// they would have without any wind.
+  pub fn spawn_offset(&self) -> WorldUnit {
+    -self.speed * Self::FALL_TIME
+  }
+
+  fn tick(&mut self, rng: &mut fastrand::Rng, delta: Duration, max_speed: WorldUnit) {
+    if self.gust_timer.tick(delta).finished() {
+      self.target_speed = max_speed * (2. * rng.f32() - 1.);
+      let period =
+        Self::MIN_GUST_PERIOD + (Self::MAX_GUST_PERIOD - Self::MIN_GUST_PERIOD) * rng.f32();
+      self.gust_timer = Timer::from_seconds(period, TimerMode::Once);
+    }
+
+    let easing = (Self::EASING * delta.as_secs_f32()).min(1.);
+    self.speed += (self.target_speed - self.speed) * easing;
+  }
+}
+
+pub struct WindPlugin;
+
+impl WindPlugin {
+  /// How quickly raindrops are dragged to the wind speed, per second.
+  const RAIN_DRAG: f32 = 1.5;
+
+  fn reset_wind(mut wind: ResMut<Wind>) {
+    *wind = Wind::new();
+  }
+
+  fn update_wind(
+    time: Res<Time>,
+    wave: Res<Wave>,
+    mut rng: ResMut<GameRng>,
+    mut wind: ResMut<Wind>,
+  ) {
+    wind.tick(&mut rng, time.delta(), wave.settings().max_wind_speed);
+  }
+
+  fn apply_wind(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut query: Query<(&mut MoveComponent, &Rain), With<InPlay>>,
+  ) {
+    for (mut rain_vel, rain) in &mut query {
+      let drag = (Self::RAIN_DRAG * rain.kind.wind_drag() * time.delta_secs()).min(1.);
+      let delta_x = rain_vel.delta.x;
+      rain_vel.delta.x += (wind.speed - delta_x) * drag;
+    }
+  }
+}
+
+impl Plugin for WindPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .insert_resource(Wind::new())
+      .add_systems(OnEnter(InGame), Self::reset_wind)
+      .add_systems(
+        FixedUpdate,
+        (
+          Self::update_wind.in_set(DrawRandom::Wind),
+          Self::apply_wind.before(MovePlugin::apply_moves),
+        )
+          .chain()
+          .run_if(GameStatePlugin::playing),
+      );
+  }
+}